@@ -0,0 +1,457 @@
+use crate::Errors;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::meta::ParseNestedMeta;
+use syn::{Attribute, Expr, ExprLit, Ident, Lit, LitBool, LitStr, Path, Token, Type, Visibility};
+
+/// The value `build()` falls back to for a field that was never set.
+#[derive(Clone)]
+pub(crate) enum FieldDefault {
+    /// `#[builder(default)]`, which uses the field type's `Default` impl.
+    Trait,
+    /// `#[builder(default = expr)]` or `#[builder(default = "expr")]`.
+    Expr(Expr),
+}
+
+impl FieldDefault {
+    pub(crate) fn to_expr(&self) -> TokenStream2 {
+        match self {
+            FieldDefault::Trait => quote! { core::default::Default::default() },
+            FieldDefault::Expr(expr) => quote! { #expr },
+        }
+    }
+}
+
+/// Options from `setter(...)`, given on a field or on the container as the
+/// default for every field. `None` means the option was not mentioned.
+#[derive(Clone, Default)]
+pub(crate) struct SetterOptions {
+    pub(crate) into: Option<bool>,
+    pub(crate) strip_option: Option<bool>,
+    /// Only allowed on a field, since setter names must be unique.
+    pub(crate) name: Option<Ident>,
+    pub(crate) vis: Option<Visibility>,
+    /// Only allowed on a field: leave the field out of the builder and fill
+    /// it from its default.
+    pub(crate) skip: Option<bool>,
+    /// `#[builder(try_setter)]`, which is given next to `setter(...)` rather
+    /// than inside it.
+    pub(crate) try_setter: Option<bool>,
+}
+
+impl SetterOptions {
+    fn parse(&mut self, meta: ParseNestedMeta, on_field: bool) -> Result<(), syn::Error> {
+        meta.parse_nested_meta(|meta| match key(&meta)?.as_str() {
+            "into" => set_once(&mut self.into, &meta, parse_flag(&meta)?),
+            "strip_option" => set_once(&mut self.strip_option, &meta, parse_flag(&meta)?),
+            "name" if on_field => set_once(&mut self.name, &meta, parse_ident_value(&meta)?),
+            "vis" => set_once(&mut self.vis, &meta, parse_lit_str(&meta)?.parse()?),
+            "skip" if on_field => set_once(&mut self.skip, &meta, parse_flag(&meta)?),
+            _ if on_field => Err(unknown(
+                &meta,
+                &["into", "strip_option", "name", "vis", "skip"],
+            )),
+            _ => Err(unknown(&meta, &["into", "strip_option", "vis"])),
+        })
+    }
+
+    /// Field options take precedence over the container's.
+    pub(crate) fn or(&self, container: &SetterOptions) -> SetterOptions {
+        SetterOptions {
+            into: self.into.or(container.into),
+            strip_option: self.strip_option.or(container.strip_option),
+            name: self.name.clone(),
+            vis: self.vis.clone().or_else(|| container.vis.clone()),
+            skip: self.skip,
+            try_setter: self.try_setter.or(container.try_setter),
+        }
+    }
+}
+
+/// Options from `each = "..."` or `each(name = "...", item = "...")`.
+pub(crate) struct EachOptions {
+    /// The name of the per-item setter.
+    pub(crate) name: Ident,
+    /// The type the setter takes, for collections whose item type cannot be
+    /// read off the field type.
+    pub(crate) item: Option<Type>,
+}
+
+impl EachOptions {
+    fn parse(meta: &ParseNestedMeta) -> Result<EachOptions, syn::Error> {
+        if meta.input.peek(Token![=]) {
+            return Ok(EachOptions {
+                name: parse_ident_value(meta)?,
+                item: None,
+            });
+        }
+        let mut name = None;
+        let mut item = None;
+        meta.parse_nested_meta(|meta| match key(&meta)?.as_str() {
+            "name" => set_once(&mut name, &meta, parse_ident_value(&meta)?),
+            "item" => set_once(&mut item, &meta, parse_lit_str(&meta)?.parse()?),
+            _ => Err(unknown(&meta, &["name", "item"])),
+        })?;
+        match name {
+            Some(name) => Ok(EachOptions { name, item }),
+            None => Err(meta.error(r#"expected `each(name = "...")`"#)),
+        }
+    }
+}
+
+/// Options from `#[builder(...)]` on a field.
+#[derive(Default)]
+pub(crate) struct FieldAttribute {
+    pub(crate) name: Option<Ident>,
+    pub(crate) each: Option<EachOptions>,
+    pub(crate) default: Option<FieldDefault>,
+    pub(crate) setter: SetterOptions,
+    /// `sub_builder`, kept for its span.
+    pub(crate) sub_builder: Option<Path>,
+    /// `private`, which makes the setters private to the defining module.
+    pub(crate) private: Option<Path>,
+    /// `optional`, for a field whose type is an `Option` under another name.
+    pub(crate) optional: Option<Path>,
+    /// `required`, for an `Option` field that must be set all the same.
+    pub(crate) required: Option<Path>,
+    /// `constructor_arg`, for a field passed to the builder function instead
+    /// of being set afterwards.
+    pub(crate) constructor_arg: Option<Path>,
+    pub(crate) env: Option<FieldEnv>,
+}
+
+impl FieldAttribute {
+    /// Parses every `#[builder]` attribute of a field, recording problems in
+    /// `errors` and carrying on with the next attribute.
+    pub(crate) fn from_attrs(attrs: &[Attribute], errors: &mut Errors) -> FieldAttribute {
+        let mut field_attribute = FieldAttribute::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("builder")) {
+            if let Err(err) = attr.parse_nested_meta(|meta| field_attribute.parse(meta, attr)) {
+                errors.push(err);
+            }
+        }
+        field_attribute
+    }
+
+    fn parse(&mut self, meta: ParseNestedMeta, attr: &Attribute) -> Result<(), syn::Error> {
+        match key(&meta)?.as_str() {
+            "name" => set_once(&mut self.name, &meta, parse_ident_value(&meta)?),
+            "each" => set_once(&mut self.each, &meta, EachOptions::parse(&meta)?),
+            "default" => {
+                let default = if meta.input.peek(Token![=]) {
+                    // A string literal holds the expression as source text, the
+                    // same way `each` names its method.
+                    match meta.value()?.parse()? {
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Str(lit), ..
+                        }) => FieldDefault::Expr(lit.parse()?),
+                        expr => FieldDefault::Expr(expr),
+                    }
+                } else {
+                    FieldDefault::Trait
+                };
+                set_once(&mut self.default, &meta, default)
+            }
+            "setter" => self.setter.parse(meta, true),
+            "try_setter" => set_once(&mut self.setter.try_setter, &meta, parse_flag(&meta)?),
+            "sub_builder" => set_once(&mut self.sub_builder, &meta, meta.path.clone()),
+            "private" => set_once(&mut self.private, &meta, meta.path.clone()),
+            "optional" => set_once(&mut self.optional, &meta, meta.path.clone()),
+            "required" => set_once(&mut self.required, &meta, meta.path.clone()),
+            "constructor_arg" => set_once(&mut self.constructor_arg, &meta, meta.path.clone()),
+            "env" => set_once(&mut self.env, &meta, FieldEnv::parse(&meta)?),
+            // The message and span of the field attribute from before it had
+            // any option but `each`, which callers may already rely on.
+            _ => Err(syn::Error::new_spanned(
+                &attr.meta,
+                r#"expected `builder(each = "...")`"#,
+            )),
+        }
+    }
+}
+
+/// The `env` option of a field.
+pub(crate) enum FieldEnv {
+    /// `env = "..."`, the variable that `from_env` and `from_map` read the
+    /// field from.
+    Var(LitStr),
+    /// `env = false`, which leaves the field out of the container's
+    /// `env_prefix`.
+    Skip,
+}
+
+impl FieldEnv {
+    fn parse(meta: &ParseNestedMeta) -> Result<FieldEnv, syn::Error> {
+        let value = meta.value()?;
+        if value.peek(LitStr) {
+            return Ok(FieldEnv::Var(value.parse()?));
+        }
+        match value.parse::<LitBool>() {
+            Ok(lit) if !lit.value => Ok(FieldEnv::Skip),
+            _ => Err(meta.error(r#"expected `env = "..."` or `env = false`"#)),
+        }
+    }
+}
+
+/// How the generated setters and `build` take the builder.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub(crate) enum Pattern {
+    /// `fn field(self, ..) -> Self` and `fn build(self)`, moving every field
+    /// into the target without cloning.
+    Owned,
+    /// `fn field(&mut self, ..) -> &mut Self` and `fn build(&self)`.
+    #[default]
+    Mutable,
+    /// `fn field(&self, ..) -> Self` and `fn build(&self)`, leaving the
+    /// original builder untouched by every call.
+    Immutable,
+}
+
+/// Options from `build_fn(...)` on the container.
+#[derive(Default)]
+pub(crate) struct BuildFnOptions {
+    pub(crate) name: Option<Ident>,
+    /// A function taking `&Builder` and returning `Result<(), E>`, run by
+    /// `build` once every required field is set. `E` must implement Display
+    /// for the generated error enum, which keeps only the message, or be
+    /// convertible into the custom error type, which keeps `E` itself.
+    pub(crate) validate: Option<Path>,
+    /// A type implementing `From<UninitializedFieldError>`, `From<E>` for the
+    /// error of `validate`, and `From<ValidationError>` if there are
+    /// sub-builders, that `build` returns instead of the generated error enum.
+    pub(crate) error: Option<Path>,
+}
+
+impl BuildFnOptions {
+    fn parse(&mut self, meta: ParseNestedMeta) -> Result<(), syn::Error> {
+        meta.parse_nested_meta(|meta| match key(&meta)?.as_str() {
+            "name" => set_once(&mut self.name, &meta, parse_ident_value(&meta)?),
+            "validate" => set_once(&mut self.validate, &meta, parse_path_value(&meta)?),
+            "error" => set_once(&mut self.error, &meta, parse_path_value(&meta)?),
+            _ => Err(unknown(&meta, &["name", "validate", "error"])),
+        })
+    }
+}
+
+/// Options from `builder_fn(...)` on the container.
+#[derive(Default)]
+pub(crate) struct BuilderFnOptions {
+    /// The name of the associated function on the derived struct that
+    /// returns a new builder.
+    pub(crate) name: Option<Ident>,
+}
+
+impl BuilderFnOptions {
+    fn parse(&mut self, meta: ParseNestedMeta) -> Result<(), syn::Error> {
+        meta.parse_nested_meta(|meta| match key(&meta)?.as_str() {
+            "name" => set_once(&mut self.name, &meta, parse_ident_value(&meta)?),
+            _ => Err(unknown(&meta, &["name"])),
+        })
+    }
+}
+
+/// Options from `#[builder(...)]` on the struct or enum.
+#[derive(Default)]
+pub(crate) struct ContainerAttribute {
+    /// The name of a struct's builder, in place of `{Struct}Builder`.
+    pub(crate) name: Option<Ident>,
+    /// The visibility of the builder, its error type and the functions
+    /// creating and building it, in place of the derived type's own.
+    pub(crate) vis: Option<Visibility>,
+    /// Extra traits to derive on the builder.
+    pub(crate) derive: Option<Vec<Path>>,
+    pub(crate) typestate: Option<Path>,
+    pub(crate) pattern: Option<Pattern>,
+    pub(crate) setter: SetterOptions,
+    pub(crate) build_fn: BuildFnOptions,
+    pub(crate) builder_fn: BuilderFnOptions,
+    /// `serde`, kept for its span: derive `Deserialize` on the builder and
+    /// generate `merge`.
+    pub(crate) serde: Option<Path>,
+    /// `to_builder`, kept for its span: generate `From<Type>` for the builder
+    /// and `Type::to_builder`.
+    pub(crate) to_builder: Option<Path>,
+    /// `no_std`, kept for its span: refer only to `core` and `alloc` in the
+    /// generated code.
+    pub(crate) no_std: Option<Path>,
+    /// `env_prefix = "..."`: read every field without an `env` variable of
+    /// its own from the prefix followed by the field name in upper case.
+    pub(crate) env_prefix: Option<LitStr>,
+}
+
+impl ContainerAttribute {
+    pub(crate) fn from_attrs(attrs: &[Attribute], errors: &mut Errors) -> ContainerAttribute {
+        let mut container_attribute = ContainerAttribute::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("builder")) {
+            if let Err(err) = attr.parse_nested_meta(|meta| container_attribute.parse(meta)) {
+                errors.push(err);
+            }
+        }
+        container_attribute
+    }
+
+    fn parse(&mut self, meta: ParseNestedMeta) -> Result<(), syn::Error> {
+        match key(&meta)?.as_str() {
+            "name" => set_once(&mut self.name, &meta, parse_ident_value(&meta)?),
+            "vis" => set_once(&mut self.vis, &meta, parse_lit_str(&meta)?.parse()?),
+            "derive" => {
+                let mut derives = Vec::new();
+                meta.parse_nested_meta(|meta| {
+                    derives.push(meta.path);
+                    Ok(())
+                })?;
+                set_once(&mut self.derive, &meta, derives)
+            }
+            "typestate" => set_once(&mut self.typestate, &meta, meta.path.clone()),
+            "pattern" => {
+                let lit = parse_lit_str(&meta)?;
+                let pattern = match lit.value().as_str() {
+                    "owned" => Pattern::Owned,
+                    "mutable" => Pattern::Mutable,
+                    "immutable" => Pattern::Immutable,
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            lit,
+                            r#"expected `"owned"`, `"mutable"` or `"immutable"`"#,
+                        ))
+                    }
+                };
+                set_once(&mut self.pattern, &meta, pattern)
+            }
+            "setter" => self.setter.parse(meta, false),
+            "try_setter" => set_once(&mut self.setter.try_setter, &meta, parse_flag(&meta)?),
+            "build_fn" => self.build_fn.parse(meta),
+            "builder_fn" => self.builder_fn.parse(meta),
+            "to_builder" => set_once(&mut self.to_builder, &meta, meta.path.clone()),
+            "no_std" => set_once(&mut self.no_std, &meta, meta.path.clone()),
+            "env_prefix" => set_once(&mut self.env_prefix, &meta, parse_lit_str(&meta)?),
+            "serde" if cfg!(feature = "serde") => {
+                set_once(&mut self.serde, &meta, meta.path.clone())
+            }
+            "serde" => {
+                Err(meta
+                    .error("`serde` requires the `serde` feature of derive_builder to be enabled"))
+            }
+            _ => Err(unknown(
+                &meta,
+                &[
+                    "name",
+                    "vis",
+                    "derive",
+                    "typestate",
+                    "pattern",
+                    "setter",
+                    "try_setter",
+                    "build_fn",
+                    "builder_fn",
+                    "serde",
+                    "to_builder",
+                    "no_std",
+                    "env_prefix",
+                ],
+            )),
+        }
+    }
+}
+
+/// The option name at the start of `meta`, which is always a single
+/// identifier. The helpers below rely on this having been checked.
+fn key(meta: &ParseNestedMeta) -> Result<String, syn::Error> {
+    match meta.path.get_ident() {
+        Some(ident) => Ok(ident.to_string()),
+        None => Err(meta.error("expected an option name")),
+    }
+}
+
+fn option_name(meta: &ParseNestedMeta) -> String {
+    let path = &meta.path;
+    quote!(#path).to_string()
+}
+
+/// The error for an option that does not exist, suggesting the closest of the
+/// `expected` options if the name looks like a typo of it. Only the suggestion
+/// depends on the list, so adding an option leaves the other messages alone.
+fn unknown(meta: &ParseNestedMeta, expected: &[&str]) -> syn::Error {
+    let name = option_name(meta);
+    let suggestion = expected
+        .iter()
+        .map(|key| (edit_distance(&name, key), key))
+        .filter(|&(distance, _)| distance <= 2)
+        .min_by_key(|&(distance, _)| distance);
+    match suggestion {
+        Some((_, key)) => meta.error(format!(
+            "unknown builder option `{}`, did you mean `{}`?",
+            name, key,
+        )),
+        None => meta.error(format!("unknown builder option `{}`", name)),
+    }
+}
+
+/// The number of single-character insertions, deletions and substitutions
+/// that turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn set_once<T>(slot: &mut Option<T>, meta: &ParseNestedMeta, value: T) -> Result<(), syn::Error> {
+    if slot.is_some() {
+        return Err(meta.error(format!("duplicate builder option `{}`", option_name(meta),)));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+/// Parses `key = "..."`.
+fn parse_lit_str(meta: &ParseNestedMeta) -> Result<LitStr, syn::Error> {
+    match meta.value()?.parse()? {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => Ok(lit),
+        expr => Err(syn::Error::new_spanned(
+            expr,
+            format!(
+                r#"expected a string literal, as in `{} = "..."`"#,
+                option_name(meta),
+            ),
+        )),
+    }
+}
+
+/// Parses `key = "ident"`.
+fn parse_ident_value(meta: &ParseNestedMeta) -> Result<Ident, syn::Error> {
+    let lit = parse_lit_str(meta)?;
+    lit.parse().map_err(|_| {
+        syn::Error::new_spanned(&lit, format!("`{}` is not a valid identifier", lit.value()))
+    })
+}
+
+/// Parses `flag` or `flag = true|false`.
+fn parse_flag(meta: &ParseNestedMeta) -> Result<bool, syn::Error> {
+    if meta.input.peek(Token![=]) {
+        let lit: LitBool = meta.value()?.parse()?;
+        Ok(lit.value)
+    } else {
+        Ok(true)
+    }
+}
+
+/// Parses `key = path` or `key = "path"`.
+fn parse_path_value(meta: &ParseNestedMeta) -> Result<Path, syn::Error> {
+    let value = meta.value()?;
+    if value.peek(LitStr) {
+        value.parse::<LitStr>()?.parse()
+    } else {
+        value.parse()
+    }
+}
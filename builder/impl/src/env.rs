@@ -0,0 +1,291 @@
+use crate::attr::{FieldEnv, Pattern};
+use crate::{
+    collection_args, inner_type, BuilderField, BuilderTarget, ContainerAttribute, EachItem, Errors,
+};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{DeriveInput, Ident, Type};
+
+// With `env = "..."` on a field or `env_prefix = "..."` on the container, the
+// builder can be filled from text variables:
+//
+//     fn from_map(map: &HashMap<String, String>) -> Result<Self, EnvError>
+//     fn from_env() -> Result<Self, EnvError>
+//
+// Each variable that is present is parsed with `FromStr` and passed to the
+// field's setter, so the value goes through the same conversions as a call
+// made by hand. An `each` field takes a comma-separated list and calls its
+// per-item setter for every item, with maps written as `key=value` items.
+// Every variable that fails to parse is reported, not only the first. A field
+// is left out of `env_prefix` with `env = false`.
+
+/// The variable a field is read from, if any: its own `env`, or else the
+/// container's prefix followed by the field name in upper case. Sub-builders
+/// and fields with `env = false` are never read from variables.
+fn env_var(f: &BuilderField, container: &ContainerAttribute) -> Option<String> {
+    match (&f.attr.env, &container.env_prefix) {
+        (Some(FieldEnv::Var(env)), _) => Some(env.value()),
+        (Some(FieldEnv::Skip), _) => None,
+        (None, Some(prefix)) if f.sub_builder.is_none() => Some(format!(
+            "{}{}",
+            prefix.value(),
+            f.name.unraw().to_string().to_uppercase(),
+        )),
+        _ => None,
+    }
+}
+
+/// The variable given with `env = "..."`, kept for its span.
+fn explicit_var<'a>(f: &'a BuilderField) -> Option<&'a syn::LitStr> {
+    match &f.attr.env {
+        Some(FieldEnv::Var(env)) => Some(env),
+        _ => None,
+    }
+}
+
+/// Reports the options that cannot be combined with reading a builder from
+/// variables, which needs a builder function without arguments, setters that
+/// keep the builder's type, and the standard library.
+pub(crate) fn check(
+    container: &ContainerAttribute,
+    targets: &[BuilderTarget],
+    errors: &mut Errors,
+) {
+    let all_fields = || {
+        targets
+            .iter()
+            .flat_map(|t| t.fields.iter().chain(&t.skipped_fields))
+    };
+    for f in all_fields() {
+        if let Some(env) = explicit_var(f) {
+            if f.sub_builder.is_some() || f.is_skipped() {
+                errors.push(syn::Error::new_spanned(
+                    env,
+                    "`env` cannot be combined with `sub_builder` or `setter(skip)`",
+                ));
+            }
+        }
+    }
+    let Some(span) = container
+        .env_prefix
+        .as_ref()
+        .or_else(|| all_fields().find_map(explicit_var))
+    else {
+        return;
+    };
+    if container.typestate.is_some() {
+        errors.push(syn::Error::new_spanned(
+            span,
+            "typestate builders cannot be read from variables because their setters change the builder's type",
+        ));
+    }
+    if container.no_std.is_some() {
+        errors.push(syn::Error::new_spanned(
+            span,
+            "reading a builder from variables needs the standard library, unlike `no_std`",
+        ));
+    }
+    // A field is parsed as its own type, or as the `T` of an `Option<T>`, so
+    // its type has to be visible and a collection has to go through `each`.
+    for f in targets.iter().flat_map(|t| &t.fields) {
+        if f.each.is_some() || env_var(f, container).is_none() {
+            continue;
+        }
+        if f.attr.optional.is_some() && inner_type("Option", &f.ty).is_none() {
+            errors.push(syn::Error::new_spanned(
+                &f.ty,
+                "cannot tell which type to parse for this `optional` field, use `env = false` to leave it out",
+            ));
+        } else if collection_args(&f.ty).is_some() {
+            errors.push(syn::Error::new_spanned(
+                &f.ty,
+                "a collection is read from a variable item by item through `each`, use `each` or `env = false`",
+            ));
+        }
+    }
+    for f in all_fields() {
+        if let Some(constructor_arg) = &f.attr.constructor_arg {
+            errors.push(syn::Error::new_spanned(
+                constructor_arg,
+                "`constructor_arg` cannot be used with `env` or `env_prefix`, since `from_env` starts from a builder without arguments",
+            ));
+        }
+    }
+}
+
+/// `from_map` and `from_env` on the builder, if any of its fields is read
+/// from a variable.
+pub(crate) fn impl_env(
+    ast: &DeriveInput,
+    target: &BuilderTarget,
+    container: &ContainerAttribute,
+) -> Option<TokenStream2> {
+    let vars: Vec<_> = target
+        .fields
+        .iter()
+        .filter_map(|f| Some((f, env_var(f, container)?)))
+        .collect();
+    if vars.is_empty() {
+        return None;
+    }
+
+    let builder_type_name = &target.builder_type_name;
+    let builder_fn = &target.builder_fn;
+    let vis = &target.vis;
+    let type_name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let pattern = container.pattern.unwrap_or_default();
+
+    // Calls a setter on `__builder`, which the owned and immutable patterns
+    // hand back as a new value.
+    let apply = |method: &Ident, args: TokenStream2| match pattern {
+        Pattern::Owned | Pattern::Immutable => quote! { __builder = __builder.#method(#args); },
+        Pattern::Mutable => quote! { __builder.#method(#args); },
+    };
+
+    let mut parsed_types: Vec<&Type> = Vec::new();
+    let field_reads = vars.iter().map(|(f, var)| {
+        let name = f.name.unraw().to_string();
+        // Evaluates to `Some` of the parsed value, or records the error and
+        // evaluates to `None`.
+        let parse = |ty: &Type, text: TokenStream2| {
+            quote! {
+                match <#ty as core::str::FromStr>::from_str(#text) {
+                    core::result::Result::Ok(value) => core::option::Option::Some(value),
+                    core::result::Result::Err(err) => {
+                        __errors.push(::derive_builder::EnvFieldError::new(
+                            #name,
+                            #var,
+                            std::string::ToString::to_string(&err),
+                        ));
+                        core::option::Option::None
+                    }
+                }
+            }
+        };
+        let read = match &f.each {
+            Some(each) => {
+                let method = &each.method;
+                let item = match &each.item {
+                    EachItem::Item(ty) => {
+                        parsed_types.push(ty);
+                        let parse_item = parse(ty, quote! { __item });
+                        let apply = apply(method, quote! { __item });
+                        quote! {
+                            if let core::option::Option::Some(__item) = #parse_item {
+                                #apply
+                            }
+                        }
+                    }
+                    EachItem::Entry(key_ty, value_ty) => {
+                        parsed_types.push(key_ty);
+                        parsed_types.push(value_ty);
+                        let parse_key = parse(key_ty, quote! { __key.trim() });
+                        let parse_value = parse(value_ty, quote! { __value.trim() });
+                        let apply = apply(method, quote! { __key, __value });
+                        quote! {
+                            match __item.split_once('=') {
+                                core::option::Option::Some((__key, __value)) => {
+                                    if let (core::option::Option::Some(__key), core::option::Option::Some(__value)) = (#parse_key, #parse_value) {
+                                        #apply
+                                    }
+                                }
+                                core::option::Option::None => {
+                                    __errors.push(::derive_builder::EnvFieldError::new(
+                                        #name,
+                                        #var,
+                                        std::format!("expected `key=value`, found `{}`", __item),
+                                    ));
+                                }
+                            }
+                        }
+                    }
+                };
+                quote! {
+                    for __item in __text.split(',').map(|item| item.trim()).filter(|item| !item.is_empty()) {
+                        #item
+                    }
+                }
+            }
+            None => {
+                // The setter of an `Option<T>` field takes a bare `T` unless
+                // `strip_option` was turned off or the field is `required`.
+                let (_, strip_option) = f.setter_flags(container);
+                let ty = inner_type("Option", &f.ty).unwrap_or(&f.ty);
+                parsed_types.push(ty);
+                let value = match (inner_type("Option", &f.ty), f.option_inner()) {
+                    (Some(_), Some(_)) if strip_option => quote! { __value },
+                    (Some(_), _) => quote! { core::option::Option::Some(__value) },
+                    (None, _) => quote! { __value },
+                };
+                let parse_value = parse(ty, quote! { __text });
+                let apply = apply(f.setter_name(), value);
+                quote! {
+                    if let core::option::Option::Some(__value) = #parse_value {
+                        #apply
+                    }
+                }
+            }
+        };
+        quote! {
+            if let core::option::Option::Some(__text) = map.get(#var) {
+                #read
+            }
+        }
+    })
+    .collect::<Vec<_>>();
+
+    let default_types = target.collection_types();
+    // Spanned at the field types, so that a type without `FromStr` is
+    // reported there rather than at the derive.
+    let parse_bounds = parsed_types.iter().map(|ty| {
+        quote_spanned! {ty.span()=>
+            #ty: core::str::FromStr,
+            <#ty as core::str::FromStr>::Err: core::fmt::Display,
+        }
+    });
+    let bounds = quote! {
+        #(#default_types: core::default::Default,)*
+        #(#parse_bounds)*
+    };
+    let var_names = vars.iter().map(|(_, var)| var);
+
+    Some(quote! {
+        impl #impl_generics #builder_type_name #ty_generics #where_clause {
+            /// Starts a new builder and sets every field whose variable is in
+            /// `map`, parsing the values with `FromStr`.
+            #vis fn from_map(
+                map: &std::collections::HashMap<std::string::String, std::string::String>,
+            ) -> core::result::Result<Self, ::derive_builder::EnvError>
+            where
+                #bounds
+            {
+                let mut __builder = <#type_name #ty_generics>::#builder_fn();
+                let mut __errors = std::vec::Vec::new();
+                #(#field_reads)*
+                if __errors.is_empty() {
+                    core::result::Result::Ok(__builder)
+                } else {
+                    core::result::Result::Err(::derive_builder::EnvError::new(__errors))
+                }
+            }
+
+            /// Like `from_map`, with the variables of the process environment.
+            /// A variable that is not valid unicode counts as unset.
+            #vis fn from_env() -> core::result::Result<Self, ::derive_builder::EnvError>
+            where
+                #bounds
+            {
+                let mut map = std::collections::HashMap::new();
+                for var in [#(#var_names),*] {
+                    if let core::result::Result::Ok(value) = std::env::var(var) {
+                        map.insert(std::string::String::from(var), value);
+                    }
+                }
+                Self::from_map(&map)
+            }
+        }
+    })
+}
@@ -0,0 +1,1538 @@
+use proc_macro::TokenStream;
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote, quote_spanned};
+use std::ops::Deref;
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Data, DataStruct, DeriveInput, Field, Fields, GenericArgument, GenericParam,
+    Generics, Ident, Index, Member, Path, PathArguments, PathSegment, Type, TypePath, Visibility,
+};
+
+mod attr;
+mod env;
+mod typestate;
+
+use crate::attr::{ContainerAttribute, EachOptions, FieldAttribute, FieldDefault, Pattern};
+
+#[proc_macro_derive(Builder, attributes(builder))]
+pub fn derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    match impl_builder(&ast) {
+        Ok(ts) => ts.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// The type argument of `wrapper<T>`, also when the wrapper is named by a
+/// qualified path such as `std::option::Option<T>`.
+fn inner_type<'a>(wrapper: &str, ty: &'a Type) -> Option<&'a Type> {
+    if let Type::Path(TypePath {
+        qself: None,
+        path: Path { ref segments, .. },
+    }) = ty
+    {
+        if let Some(PathSegment { ident, arguments }) = segments.last() {
+            if ident == wrapper {
+                if let PathArguments::AngleBracketed(arg) = arguments {
+                    if let Some(GenericArgument::Type(typ)) = arg.args.first() {
+                        return Some(typ);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Whether `ty` is certainly not an `Option`: a type that is not a path, a
+/// generic type other than `Option`, or a primitive. Any other name may be an
+/// alias of `Option`.
+fn visibly_not_option(ty: &Type) -> bool {
+    const PRIMITIVES: &[&str] = &[
+        "bool", "char", "str", "String", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16",
+        "i32", "i64", "i128", "isize", "f32", "f64",
+    ];
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => match path.segments.last() {
+            Some(PathSegment { ident, arguments }) => match arguments {
+                PathArguments::None => PRIMITIVES.iter().any(|primitive| ident == primitive),
+                _ => ident != "Option",
+            },
+            None => false,
+        },
+        Type::Paren(ty) => visibly_not_option(&ty.elem),
+        Type::Group(ty) => visibly_not_option(&ty.elem),
+        Type::Macro(_) | Type::Verbatim(_) => false,
+        _ => true,
+    }
+}
+
+/// Collects errors so that every problem with the input is reported by a
+/// single compile instead of one at a time.
+#[derive(Default)]
+struct Errors(Option<syn::Error>);
+
+impl Errors {
+    fn push(&mut self, error: syn::Error) {
+        match &mut self.0 {
+            Some(errors) => errors.combine(error),
+            None => self.0 = Some(error),
+        }
+    }
+
+    fn finish(self) -> Result<(), syn::Error> {
+        match self.0 {
+            Some(errors) => Err(errors),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Converts a `CamelCase` identifier into `snake_case`, keeping acronyms such
+/// as the `HTTP` in `HTTPRequest` together.
+fn to_snake_case(ident: &Ident) -> String {
+    let chars: Vec<char> = ident.to_string().chars().collect();
+    let mut snake = String::new();
+    for (i, &ch) in chars.iter().enumerate() {
+        if ch.is_uppercase() {
+            let after_lower = i > 0 && !chars[i - 1].is_uppercase() && chars[i - 1] != '_';
+            let before_lower = i > 0
+                && chars[i - 1].is_uppercase()
+                && chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if after_lower || before_lower {
+                snake.push('_');
+            }
+            snake.extend(ch.to_lowercase());
+        } else {
+            snake.push(ch);
+        }
+    }
+    snake
+}
+
+/// Renders a type the way it is usually written, as `Vec<String>` rather than
+/// the `Vec < String >` of its token stream.
+fn type_to_string(ty: &Type) -> String {
+    let mut string = quote!(#ty).to_string();
+    for (spaced, tight) in [
+        (" <", "<"),
+        ("< ", "<"),
+        (" >", ">"),
+        (" ,", ","),
+        (" ::", "::"),
+        (":: ", "::"),
+        ("& ", "&"),
+        ("( ", "("),
+        (" )", ")"),
+        ("[ ", "["),
+        (" ]", "]"),
+        (" ;", ";"),
+    ] {
+        string = string.replace(spaced, tight);
+    }
+    string
+}
+
+/// Whether `ty` mentions a parameter of `generics`. A bound on a type that
+/// does not is either trivially true or an error at the derive, so such types
+/// are checked where they are used instead.
+fn mentions_generics(ty: &Type, generics: &Generics) -> bool {
+    fn walk(tokens: TokenStream2, params: &[&Ident]) -> bool {
+        tokens.into_iter().any(|token| match token {
+            TokenTree::Ident(ident) => params.contains(&&ident),
+            TokenTree::Group(group) => walk(group.stream(), params),
+            _ => false,
+        })
+    }
+    let params: Vec<&Ident> = generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Type(param) => &param.ident,
+            GenericParam::Lifetime(param) => &param.lifetime.ident,
+            GenericParam::Const(param) => &param.ident,
+        })
+        .collect();
+    walk(quote!(#ty), &params)
+}
+
+/// The parameter type and converted value of a setter taking `ty`.
+fn setter_arg(ty: &Type, arg: &Ident, into: bool) -> (TokenStream2, TokenStream2) {
+    if into {
+        (
+            quote! { impl core::convert::Into<#ty> },
+            quote! { core::convert::Into::into(#arg) },
+        )
+    } else {
+        (quote! { #ty }, quote! { #arg })
+    }
+}
+
+/// The per-item setter requested with `#[builder(each = "...")]`. Items are
+/// added through `Extend`, so any collection with `Extend` and `Default`
+/// impls can be filled this way.
+struct Each {
+    method: Ident,
+    item: EachItem,
+}
+
+/// What the per-item setter of an `each` field takes.
+#[allow(clippy::large_enum_variant)]
+enum EachItem {
+    /// One element of a list or set, or whatever was given as `item = "..."`.
+    Item(Type),
+    /// A key and a value, inserted into a map as one entry.
+    Entry(Type, Type),
+}
+
+impl Each {
+    /// Works out the item type from the standard collections, unless it was
+    /// given explicitly.
+    fn new(options: &EachOptions, ty: &Type) -> Result<Each, syn::Error> {
+        let item = match (&options.item, collection_args(ty).as_deref()) {
+            (Some(item), _) => EachItem::Item(item.clone()),
+            (None, Some([item])) => EachItem::Item((*item).clone()),
+            (None, Some([key, value])) => EachItem::Entry((*key).clone(), (*value).clone()),
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    r#"cannot tell the item type of this collection, use `each(name = "...", item = "...")`"#,
+                ))
+            }
+        };
+        Ok(Each {
+            method: options.name.clone(),
+            item,
+        })
+    }
+
+    /// The type of the items the collection is extended with.
+    fn item_type(&self) -> TokenStream2 {
+        match &self.item {
+            EachItem::Item(ty) => quote! { #ty },
+            EachItem::Entry(key_ty, value_ty) => quote! { (#key_ty, #value_ty) },
+        }
+    }
+
+    /// The parameters of the per-item setter, and the item built from them.
+    fn setter_args(&self, into: bool) -> (TokenStream2, TokenStream2) {
+        let method = &self.method;
+        match &self.item {
+            EachItem::Item(ty) => {
+                let (arg_ty, value) = setter_arg(ty, method, into);
+                (quote! { #method: #arg_ty }, value)
+            }
+            EachItem::Entry(key_ty, value_ty) => {
+                let (key_ty, key) = setter_arg(key_ty, &format_ident!("key"), into);
+                let (value_ty, value) = setter_arg(value_ty, &format_ident!("value"), into);
+                (
+                    quote! { key: #key_ty, value: #value_ty },
+                    quote! { (#key, #value) },
+                )
+            }
+        }
+    }
+}
+
+/// The type arguments of the standard collections `each` knows how to fill:
+/// the element type of a list or set, or the key and value types of a map.
+fn collection_args(ty: &Type) -> Option<Vec<&Type>> {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    let PathSegment { ident, arguments } = path.segments.last()?;
+    let arity = match ident.to_string().as_str() {
+        "Vec" | "VecDeque" | "LinkedList" | "HashSet" | "BTreeSet" | "BinaryHeap" => 1,
+        "HashMap" | "BTreeMap" => 2,
+        _ => return None,
+    };
+    let PathArguments::AngleBracketed(args) = arguments else {
+        return None;
+    };
+    let types: Vec<&Type> = args
+        .args
+        .iter()
+        .filter_map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        })
+        .take(arity)
+        .collect();
+    (types.len() == arity).then_some(types)
+}
+
+/// The builder of a field marked `#[builder(sub_builder)]`. The field's type
+/// must derive `Builder` itself, and its builder is reached through the
+/// companion crate's traits rather than by name, since the inner type may
+/// rename its `build`, return its own error or use any pattern.
+struct SubBuilder {
+    /// `<Inner as Buildable>::Builder`, spanned at the field type so that a
+    /// type without a builder is reported there.
+    builder_ty: Type,
+}
+
+impl SubBuilder {
+    fn new(ty: &Type) -> SubBuilder {
+        SubBuilder {
+            builder_ty: syn::parse_quote_spanned! {ty.span()=>
+                <#ty as ::derive_builder::__private::Buildable>::Builder
+            },
+        }
+    }
+}
+
+/// A field of the derived struct or variant.
+struct BuilderField<'a> {
+    field: &'a Field,
+    attr: FieldAttribute,
+    /// The name of the field in the builder and of its setter: the field's
+    /// own name, `_0`, `_1`, ... for tuple fields, or the one given by
+    /// `#[builder(name = "...")]`.
+    name: Ident,
+    /// How `build` refers to the field when constructing the target.
+    member: Member,
+    each: Option<Each>,
+    sub_builder: Option<SubBuilder>,
+}
+
+impl Deref for BuilderField<'_> {
+    type Target = Field;
+
+    fn deref(&self) -> &Field {
+        self.field
+    }
+}
+
+impl BuilderField<'_> {
+    /// An optional field starts out as `None` and may be left unset: an
+    /// `Option` field not marked `required`, or any field marked `optional`.
+    fn is_optional(&self) -> bool {
+        self.option_inner().is_some() || self.attr.optional.is_some()
+    }
+
+    /// The `T` of an optional `Option<T>` field. It is unknown for a field
+    /// marked `optional` whose type is an alias.
+    fn option_inner(&self) -> Option<&Type> {
+        match self.attr.required {
+            Some(_) => None,
+            None => inner_type("Option", &self.ty),
+        }
+    }
+
+    /// The builder stores a field as `Option<T>` to remember whether it was
+    /// set, except for optional fields, which start out as `None` anyway,
+    /// sub-builders, which keep track of their own fields, and constructor
+    /// arguments, which are set from the start. An optional field with an
+    /// explicit default is tracked after all, so that an untouched default can
+    /// be told apart from a value that was set, as `merge` needs to.
+    fn is_tracked(&self) -> bool {
+        (!self.is_optional() || self.attr.default.is_some())
+            && self.sub_builder.is_none()
+            && self.attr.constructor_arg.is_none()
+    }
+
+    /// What `build` takes for a tracked field that was never set: its explicit
+    /// default, or an empty collection for `each`.
+    fn fallback(&self) -> Option<TokenStream2> {
+        match (&self.attr.default, &self.each) {
+            (Some(default), _) => Some(default.to_expr()),
+            (None, Some(_)) => Some(quote! { core::default::Default::default() }),
+            (None, None) => None,
+        }
+    }
+
+    /// The value stored by the setter for the whole field, given the value it
+    /// was called with, after conversion.
+    fn setter_value(&self, strip_option: bool, value: TokenStream2) -> TokenStream2 {
+        let value = if strip_option && self.option_inner().is_some() {
+            quote! { core::option::Option::Some(#value) }
+        } else {
+            value
+        };
+        if self.is_tracked() {
+            quote! { core::option::Option::Some(#value) }
+        } else {
+            value
+        }
+    }
+
+    /// The collection of an `each` field stored at `place`, which the first
+    /// item or `extend_` call fills with the collection's default.
+    fn collection(&self, place: TokenStream2) -> TokenStream2 {
+        let fallback = self.fallback();
+        quote! { #place.get_or_insert_with(|| #fallback) }
+    }
+
+    /// The `where` clause of the setters that start an `each` collection
+    /// without an explicit default from `Default`.
+    fn collection_bound(&self) -> Option<TokenStream2> {
+        let ty = &self.ty;
+        (self.each.is_some() && self.attr.default.is_none())
+            .then(|| quote! { where #ty: core::default::Default })
+    }
+
+    /// A tracked field must be set explicitly before `build()` unless it has a
+    /// default or is a collection.
+    fn is_required(&self) -> bool {
+        self.is_tracked() && self.fallback().is_none()
+    }
+
+    /// The setter options in effect for the field: whether the setter accepts
+    /// `impl Into<T>`, and whether an `Option<T>` field's setter takes a bare
+    /// `T` (the default) rather than the whole `Option`.
+    fn setter_flags(&self, container: &ContainerAttribute) -> (bool, bool) {
+        let setter = self.attr.setter.or(&container.setter);
+        (
+            setter.into.unwrap_or(false),
+            setter.strip_option.unwrap_or(true),
+        )
+    }
+
+    /// Whether the field gets a `try_` setter next to its setter.
+    fn has_try_setter(&self, container: &ContainerAttribute) -> bool {
+        let setter = self.attr.setter.or(&container.setter);
+        setter.try_setter.unwrap_or(false)
+    }
+
+    /// The type the setter for the whole field takes, before any `into`
+    /// conversion: the `T` of an optional `Option<T>` field with
+    /// `strip_option`, and otherwise the field's own type.
+    fn setter_type(&self, strip_option: bool) -> &Type {
+        match self.option_inner() {
+            Some(ty) if strip_option => ty,
+            _ => &self.ty,
+        }
+    }
+
+    /// The name of the setter taking the whole field.
+    fn setter_name(&self) -> &Ident {
+        self.attr.setter.name.as_ref().unwrap_or(&self.name)
+    }
+
+    /// Every method the builder gets for the field: the setter, or `_mut` for
+    /// a sub-builder, the per-item and `extend_` setters of `each`, and the
+    /// `try_` setter.
+    fn setter_methods(&self, container: &ContainerAttribute) -> Vec<Ident> {
+        let setter = self.setter_name();
+        if self.sub_builder.is_some() {
+            return vec![format_ident!("{}_mut", setter)];
+        }
+        let mut methods = Vec::new();
+        if let Some(each) = &self.each {
+            methods.push(each.method.clone());
+            methods.push(format_ident!("extend_{}", setter));
+        }
+        if self.each.as_ref().is_none_or(|each| each.method != *setter) {
+            methods.push(setter.clone());
+        }
+        if self.has_try_setter(container) {
+            methods.push(format_ident!("try_{}", setter));
+        }
+        methods
+    }
+
+    /// Setters are as visible as the builder unless given a visibility of
+    /// their own or marked `private`.
+    fn setter_vis(&self, container: &ContainerAttribute, target: &BuilderTarget) -> Visibility {
+        if self.attr.private.is_some() {
+            return Visibility::Inherited;
+        }
+        let setter = self.attr.setter.or(&container.setter);
+        setter.vis.unwrap_or_else(|| target.vis.clone())
+    }
+
+    fn is_skipped(&self) -> bool {
+        self.attr.setter.skip == Some(true)
+    }
+
+    /// Whether the builder holds a value for the field, judged by how it is
+    /// stored: a sub-builder counts once it could be built, and a collection
+    /// once any of its setters was called.
+    fn is_set(&self) -> TokenStream2 {
+        let name = &self.name;
+        if self.attr.constructor_arg.is_some() {
+            quote! { true }
+        } else if self.sub_builder.is_some() {
+            quote_spanned! {self.ty.span()=>
+                ::derive_builder::__private::SubBuilder::is_complete(&self.#name)
+            }
+        } else {
+            quote! { core::option::Option::is_some(&self.#name) }
+        }
+    }
+}
+
+/// The fields of a struct or variant, split into those the builder stores and
+/// those skipped with `setter(skip)`.
+fn builder_fields<'a>(
+    fields: &'a Fields,
+    errors: &mut Errors,
+) -> (Vec<BuilderField<'a>>, Vec<BuilderField<'a>>) {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let mut attr = FieldAttribute::from_attrs(&field.attrs, errors);
+            let name = match (&attr.name, &field.ident) {
+                (Some(name), _) | (None, Some(name)) => name.clone(),
+                (None, None) => format_ident!("_{}", i),
+            };
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(i)),
+            };
+            let each = attr.each.as_ref().and_then(|each| {
+                Each::new(each, &field.ty)
+                    .map_err(|err| errors.push(err))
+                    .ok()
+            });
+            let sub_builder = attr.sub_builder.as_ref().map(|sub_builder| {
+                if attr.each.is_some() || attr.default.is_some() {
+                    errors.push(syn::Error::new_spanned(
+                        sub_builder,
+                        "`sub_builder` cannot be combined with `each` or `default`",
+                    ));
+                }
+                SubBuilder::new(&field.ty)
+            });
+            if let (Some(private), Some(_)) = (&attr.private, &attr.setter.vis) {
+                errors.push(syn::Error::new_spanned(
+                    private,
+                    "`private` cannot be combined with `setter(vis)`",
+                ));
+            }
+            if attr.setter.skip == Some(true) {
+                if attr.each.is_some() || attr.sub_builder.is_some() {
+                    errors.push(syn::Error::new_spanned(
+                        &field.ty,
+                        "a field with `setter(skip)` cannot use `each` or `sub_builder`",
+                    ));
+                }
+                // A skipped field is never set, so it always takes its default.
+                attr.default.get_or_insert(FieldDefault::Trait);
+            }
+            if let Some(constructor_arg) = &attr.constructor_arg {
+                if attr.each.is_some()
+                    || attr.default.is_some()
+                    || attr.sub_builder.is_some()
+                    || attr.optional.is_some()
+                    || attr.setter.skip == Some(true)
+                {
+                    errors.push(syn::Error::new_spanned(
+                        constructor_arg,
+                        "`constructor_arg` cannot be combined with `each`, `default`, `sub_builder`, `optional` or `setter(skip)`",
+                    ));
+                }
+            }
+            if let (Some(_), Some(required)) = (&attr.optional, &attr.required) {
+                errors.push(syn::Error::new_spanned(
+                    required,
+                    "a field cannot be both `optional` and `required`",
+                ));
+            }
+            // A sub-builder has no setter for the whole field, and neither does
+            // an `each` field whose per-item setter took the field's name, so
+            // there is nothing for a `try_` setter to call.
+            if attr.setter.try_setter == Some(true) {
+                let setter = attr.setter.name.as_ref().unwrap_or(&name);
+                let each_replaces_setter = attr.each.as_ref().is_some_and(|each| &each.name == setter);
+                if attr.sub_builder.is_some() || each_replaces_setter {
+                    errors.push(syn::Error::new_spanned(
+                        &field.ty,
+                        "`try_setter` needs a setter for the whole field, which `sub_builder` and an `each` setter of the same name replace",
+                    ));
+                }
+            }
+            // Without a default, an `optional` field starts out as `None`,
+            // which only an `Option` under another name can hold.
+            if let (Some(optional), None) = (&attr.optional, &attr.default) {
+                if visibly_not_option(&field.ty) {
+                    errors.push(syn::Error::new_spanned(
+                        optional,
+                        "`optional` requires a field whose type is an `Option`, possibly under another name",
+                    ));
+                }
+            }
+            let strips = inner_type("Option", &field.ty).is_some() && attr.required.is_none();
+            if attr.setter.strip_option == Some(true) && !strips {
+                errors.push(syn::Error::new_spanned(
+                    &field.ty,
+                    "`strip_option` requires an optional field of type `Option<T>`",
+                ));
+            }
+            BuilderField {
+                field,
+                attr,
+                name,
+                member,
+                each,
+                sub_builder,
+            }
+        })
+        .partition(|f| !f.is_skipped())
+}
+
+/// One builder to generate: for a struct, or for a single variant of an enum.
+struct BuilderTarget<'a> {
+    builder_type_name: Ident,
+    /// The visibility of the builder, its error type, and the functions
+    /// creating and building it.
+    vis: Visibility,
+    /// Path of the struct or variant that `build` constructs. Tuple structs
+    /// and variants are built with the braced syntax too, as in
+    /// `Wrapper { 0: value }`.
+    constructor: TokenStream2,
+    /// The associated function on the derived type that returns a new builder.
+    builder_fn: Ident,
+    build_fn: Ident,
+    fields: Vec<BuilderField<'a>>,
+    /// Fields left out of the builder, which `build` fills from their
+    /// defaults.
+    skipped_fields: Vec<BuilderField<'a>>,
+}
+
+impl BuilderTarget<'_> {
+    fn error_type_name(&self) -> Ident {
+        format_ident!("{}Error", self.builder_type_name)
+    }
+
+    fn skipped_field_build(&self) -> Vec<TokenStream2> {
+        self.skipped_fields
+            .iter()
+            .filter_map(|f| {
+                let member = &f.member;
+                let default = f.attr.default.as_ref()?.to_expr();
+                Some(quote! { #member: #default })
+            })
+            .collect()
+    }
+
+    /// The `From<Type>` impl for the builder and `Type::to_builder`, given how
+    /// each field of the builder holds a value taken from the target. Skipped
+    /// fields are dropped and go back to their defaults on the next build.
+    fn to_builder(
+        &self,
+        ast: &DeriveInput,
+        builder_ty: TokenStream2,
+        field_value: impl Fn(&BuilderField, TokenStream2) -> TokenStream2,
+        phantom_init: Option<TokenStream2>,
+    ) -> (TokenStream2, TokenStream2) {
+        let type_name = &ast.ident;
+        let builder_type_name = &self.builder_type_name;
+        let vis = &self.vis;
+        let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+        let field_from = self.fields.iter().map(|f| {
+            let name = &f.name;
+            let member = &f.member;
+            let value = field_value(f, quote! { value.#member });
+            quote! { #name: #value }
+        });
+        let from_impl = quote! {
+            impl #impl_generics core::convert::From<#type_name #ty_generics> for #builder_ty #where_clause {
+                fn from(value: #type_name #ty_generics) -> Self {
+                    #builder_type_name {
+                        #(#field_from,)*
+                        #phantom_init
+                    }
+                }
+            }
+        };
+        let to_builder = quote! {
+            /// Returns a builder holding a copy of every field, to be changed
+            /// and built again.
+            #vis fn to_builder(&self) -> #builder_ty
+            where
+                Self: core::clone::Clone,
+            {
+                core::convert::From::from(core::clone::Clone::clone(self))
+            }
+        };
+        (from_impl, to_builder)
+    }
+
+    /// The parameters of the builder function for the `constructor_arg` fields,
+    /// in declaration order, and the initializers of those fields.
+    fn constructor_args(
+        &self,
+        container: &ContainerAttribute,
+    ) -> (Vec<TokenStream2>, Vec<TokenStream2>) {
+        self.fields
+            .iter()
+            .filter(|f| f.attr.constructor_arg.is_some())
+            .map(|f| {
+                let name = &f.name;
+                let (into, _) = f.setter_flags(container);
+                let (arg_ty, value) = setter_arg(&f.ty, name, into);
+                (quote! { #name: #arg_ty }, quote! { #name: #value })
+            })
+            .unzip()
+    }
+
+    /// The `FIELDS` constant and the `is_set_<field>`, `missing_fields` and
+    /// `is_complete` methods for an `impl` block on the builder, given how
+    /// each field tells whether it was set.
+    fn introspection(
+        &self,
+        alloc: &TokenStream2,
+        is_set: impl Fn(&BuilderField) -> TokenStream2,
+    ) -> TokenStream2 {
+        let vis = &self.vis;
+        let infos = self.fields.iter().map(|f| {
+            let name = f.name.unraw().to_string();
+            let ty = type_to_string(&f.ty);
+            let kind = if f.attr.constructor_arg.is_some() {
+                quote! { ConstructorArg }
+            } else if f.sub_builder.is_some() {
+                quote! { SubBuilder }
+            } else if f.each.is_some() {
+                quote! { Each }
+            } else if f.is_required() {
+                quote! { Required }
+            } else {
+                quote! { Optional }
+            };
+            quote! {
+                ::derive_builder::FieldInfo {
+                    name: #name,
+                    ty: #ty,
+                    kind: ::derive_builder::FieldKind::#kind,
+                }
+            }
+        });
+        let is_set_methods = self.fields.iter().map(|f| {
+            let method = format_ident!("is_set_{}", f.name);
+            let is_set = is_set(f);
+            quote! {
+                #vis fn #method(&self) -> bool {
+                    #is_set
+                }
+            }
+        });
+        let needed: Vec<_> = self
+            .fields
+            .iter()
+            .filter(|f| f.is_required() || f.sub_builder.is_some())
+            .collect();
+        let needed_len = needed.len();
+        let needed_names = needed.iter().map(|f| f.name.unraw().to_string());
+        let needed_is_set: Vec<_> = needed
+            .iter()
+            .map(|f| format_ident!("is_set_{}", f.name))
+            .collect();
+        quote! {
+            /// Every field of the builder, in declaration order.
+            #vis const FIELDS: &'static [::derive_builder::FieldInfo] = &[#(#infos),*];
+
+            #(#is_set_methods)*
+
+            /// The required fields that are not set yet, and the sub-builders
+            /// that cannot be built yet, in declaration order.
+            #vis fn missing_fields(&self) -> #alloc::vec::Vec<&'static str> {
+                let fields: [(&'static str, bool); #needed_len] = [#((#needed_names, self.#needed_is_set())),*];
+                fields
+                    .into_iter()
+                    .filter(|&(_, set)| !set)
+                    .map(|(name, _)| name)
+                    .collect()
+            }
+
+            /// Whether every required field is set, so that `build` would
+            /// not fail for a missing field.
+            #vis fn is_complete(&self) -> bool {
+                true #(&& self.#needed_is_set())*
+            }
+        }
+    }
+
+    /// Reports every setter named like one of the introspection methods, which
+    /// would otherwise clash with it without naming the field.
+    fn check_method_names(&self, container: &ContainerAttribute, errors: &mut Errors) {
+        let mut generated = vec![
+            format_ident!("FIELDS"),
+            format_ident!("missing_fields"),
+            format_ident!("is_complete"),
+        ];
+        generated.extend(
+            self.fields
+                .iter()
+                .map(|f| format_ident!("is_set_{}", f.name)),
+        );
+        for f in &self.fields {
+            for method in f.setter_methods(container) {
+                if generated.contains(&method) {
+                    errors.push(syn::Error::new(
+                        f.setter_name().span(),
+                        format!(
+                            "`{}` is also the name of an introspection method of the builder, rename the setter with `setter(name = \"...\")`",
+                            method,
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// The types of the `each` collections without an explicit default, which
+    /// start out empty through `Default` once an item is added or the builder
+    /// is built.
+    fn collection_types(&self) -> Vec<&Type> {
+        self.fields
+            .iter()
+            .filter(|f| f.each.is_some() && f.attr.default.is_none())
+            .map(|f| &f.ty)
+            .collect()
+    }
+
+    /// The types of every field defaulted through `Default`, stored or not.
+    fn trait_default_types(&self) -> Vec<&Type> {
+        self.fields
+            .iter()
+            .chain(&self.skipped_fields)
+            .filter(|f| matches!(f.attr.default, Some(FieldDefault::Trait)))
+            .map(|f| &f.ty)
+            .collect()
+    }
+}
+
+/// The builder and the function creating it, which goes into an `impl` block
+/// on the derived type shared with the other builders of an enum.
+struct BuilderOutput {
+    items: TokenStream2,
+    builder_fn: TokenStream2,
+}
+
+fn impl_builder(ast: &DeriveInput) -> Result<TokenStream2, syn::Error> {
+    //eprintln!("{:#?}", ast);
+    let type_name = &ast.ident;
+    let mut errors = Errors::default();
+    let container_attribute = ContainerAttribute::from_attrs(&ast.attrs, &mut errors);
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let vis = container_attribute
+        .vis
+        .clone()
+        .unwrap_or_else(|| ast.vis.clone());
+    let build_fn = container_attribute
+        .build_fn
+        .name
+        .clone()
+        .unwrap_or_else(|| format_ident!("build"));
+
+    // A struct has one builder named after it. An enum gets one builder per
+    // variant, named after both and created by `Enum::variant_builder()`.
+    let targets = match ast.data {
+        Data::Struct(DataStruct { ref fields, .. }) => {
+            let (fields, skipped_fields) = builder_fields(fields, &mut errors);
+            vec![BuilderTarget {
+                builder_type_name: container_attribute
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format_ident!("{}Builder", type_name)),
+                vis,
+                constructor: quote! { #type_name },
+                builder_fn: container_attribute
+                    .builder_fn
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format_ident!("builder")),
+                build_fn,
+                fields,
+                skipped_fields,
+            }]
+        }
+        Data::Enum(ref data) => {
+            if let Some(validate) = &container_attribute.build_fn.validate {
+                errors.push(syn::Error::new_spanned(
+                    validate,
+                    "`build_fn(validate)` is not supported on enums, since every variant has a builder of its own type",
+                ));
+            }
+            if let Some(to_builder) = &container_attribute.to_builder {
+                errors.push(syn::Error::new_spanned(
+                    to_builder,
+                    "`to_builder` is not supported on enums, since a builder can only hold one variant",
+                ));
+            }
+            if let Some(name) = &container_attribute.name {
+                errors.push(syn::Error::new_spanned(
+                    name,
+                    "`name` is not supported on enums, whose builders are named after their variants",
+                ));
+            }
+            if let Some(name) = &container_attribute.builder_fn.name {
+                errors.push(syn::Error::new_spanned(
+                    name,
+                    "`builder_fn(name)` is not supported on enums, whose builder functions are named after their variants",
+                ));
+            }
+            data.variants
+                .iter()
+                .map(|variant| {
+                    let variant_name = &variant.ident;
+                    let (fields, skipped_fields) = builder_fields(&variant.fields, &mut errors);
+                    BuilderTarget {
+                        builder_type_name: format_ident!("{}{}Builder", type_name, variant_name),
+                        vis: vis.clone(),
+                        constructor: quote! { #type_name::#variant_name },
+                        builder_fn: format_ident!("{}_builder", to_snake_case(variant_name)),
+                        build_fn: build_fn.clone(),
+                        fields,
+                        skipped_fields,
+                    }
+                })
+                .collect()
+        }
+        Data::Union(ref data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                "Builder cannot be derived for unions",
+            ))
+        }
+    };
+
+    if let Some(typestate) = &container_attribute.typestate {
+        if let Some(Pattern::Mutable | Pattern::Immutable) = container_attribute.pattern {
+            errors.push(syn::Error::new_spanned(
+                typestate,
+                "typestate builders always use the owned pattern",
+            ));
+        }
+        if let Some(validate) = &container_attribute.build_fn.validate {
+            errors.push(syn::Error::new_spanned(
+                validate,
+                "typestate builders cannot be validated because their `build` is infallible",
+            ));
+        }
+        if let Some(error) = &container_attribute.build_fn.error {
+            errors.push(syn::Error::new_spanned(
+                error,
+                "typestate builders cannot have a custom error because their `build` is infallible",
+            ));
+        }
+        if let Some(serde) = &container_attribute.serde {
+            errors.push(syn::Error::new_spanned(
+                serde,
+                "typestate builders cannot be deserialized because their fields change type when set",
+            ));
+        }
+        for field in targets.iter().flat_map(|target| &target.fields) {
+            if let Some(sub_builder) = &field.attr.sub_builder {
+                errors.push(syn::Error::new_spanned(
+                    sub_builder,
+                    "typestate builders cannot contain sub-builders because their `build` is infallible",
+                ));
+            }
+        }
+    }
+    for target in &targets {
+        target.check_method_names(&container_attribute, &mut errors);
+    }
+    env::check(&container_attribute, &targets, &mut errors);
+    if container_attribute.serde.is_some() {
+        for field in targets.iter().flat_map(|target| &target.fields) {
+            if let Some(constructor_arg) = &field.attr.constructor_arg {
+                errors.push(syn::Error::new_spanned(
+                    constructor_arg,
+                    "`constructor_arg` cannot be used with `serde`, which starts from a builder without arguments",
+                ));
+            }
+        }
+    }
+    errors.finish()?;
+
+    let outputs = targets.iter().map(|target| {
+        if container_attribute.typestate.is_some() {
+            typestate::impl_typestate_builder(ast, target, &container_attribute)
+        } else {
+            impl_target_builder(ast, target, &container_attribute)
+        }
+    });
+    let (items, builder_fns): (Vec<_>, Vec<_>) = outputs
+        .map(|output| (output.items, output.builder_fn))
+        .unzip();
+
+    Ok(quote! {
+        #(#items)*
+
+        impl #impl_generics #type_name #ty_generics #where_clause {
+            #(#builder_fns)*
+        }
+    })
+}
+
+fn impl_target_builder(
+    ast: &DeriveInput,
+    target: &BuilderTarget,
+    container_attribute: &ContainerAttribute,
+) -> BuilderOutput {
+    let type_name = &ast.ident;
+    let builder_type_name = &target.builder_type_name;
+    let error_type_name = target.error_type_name();
+    let constructor = &target.constructor;
+    let builder_fn = &target.builder_fn;
+    let build_fn = &target.build_fn;
+    let vis = &target.vis;
+    let generics = &ast.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let fields = &target.fields;
+    let pattern = container_attribute.pattern.unwrap_or_default();
+    // `alloc` has to be declared by the crate using `no_std`, with
+    // `extern crate alloc;`.
+    let (alloc, error_trait) = match container_attribute.no_std {
+        Some(_) => (quote! { alloc }, quote! { core::error::Error }),
+        None => (quote! { std }, quote! { std::error::Error }),
+    };
+
+    let field_names: Vec<_> = fields.iter().map(|f| &f.name).collect();
+    // Required fields are stored as `Option<T>` so that `build()` can tell an
+    // unset field apart from one that was set to its default value.
+    let field_storage_types = fields.iter().map(|f| {
+        let ty = &f.ty;
+        if let Some(sub_builder) = &f.sub_builder {
+            let builder_ty = &sub_builder.builder_ty;
+            quote! { #builder_ty }
+        } else if f.is_tracked() {
+            quote! { core::option::Option<#ty> }
+        } else {
+            quote! { #ty }
+        }
+    });
+
+    // Only the field types themselves are bounded, so a type parameter that is
+    // never cloned or defaulted does not pick up a spurious `Clone`/`Default`
+    // requirement from the derive. A field type without any parameter is not
+    // bounded at all, so that a missing `Clone` is reported at the field
+    // rather than as a trivial bound at the derive.
+    let field_types: Vec<_> = fields
+        .iter()
+        .filter(|f| f.sub_builder.is_none() && mentions_generics(&f.ty, generics))
+        .map(|f| &f.ty)
+        .collect();
+    let default_types = target.collection_types();
+    let trait_default_types = target.trait_default_types();
+    let skipped_field_build = target.skipped_field_build();
+
+    let (constructor_params, constructor_init) = target.constructor_args(container_attribute);
+    let field_name_init = fields
+        .iter()
+        .filter(|f| f.attr.constructor_arg.is_none())
+        .map(|f| {
+            let name = &f.name;
+            let ty = &f.ty;
+            if let Some(sub_builder) = &f.sub_builder {
+                let builder_ty = &sub_builder.builder_ty;
+                quote_spanned! {ty.span()=>
+                    #name: <#builder_ty as ::derive_builder::__private::SubBuilder>::new_builder()
+                }
+            } else {
+                quote! { #name: core::option::Option::None }
+            }
+        });
+
+    // Each setter is a single assignment to a builder field; the pattern only
+    // decides how the builder is taken and handed back around it.
+    let setter = |vis: &Visibility,
+                  method: &Ident,
+                  params: TokenStream2,
+                  bound: Option<TokenStream2>,
+                  assign: TokenStream2| {
+        match pattern {
+            Pattern::Owned => quote! {
+                #vis fn #method(mut self, #params) -> Self #bound {
+                    let __builder = &mut self;
+                    #assign;
+                    self
+                }
+            },
+            Pattern::Mutable => quote! {
+                #vis fn #method(&mut self, #params) -> &mut Self #bound {
+                    let __builder = &mut *self;
+                    #assign;
+                    self
+                }
+            },
+            Pattern::Immutable => quote! {
+                #vis fn #method(&self, #params) -> Self #bound {
+                    let mut __new = core::clone::Clone::clone(self);
+                    let __builder = &mut __new;
+                    #assign;
+                    __new
+                }
+            },
+        }
+    };
+
+    let field_name_method = fields.iter().map(|f| {
+        let name = &f.name;
+        let (into, strip_option) = f.setter_flags(container_attribute);
+        let method = f.setter_name();
+        let vis = &f.setter_vis(container_attribute, target);
+
+        if let Some(sub_builder) = &f.sub_builder {
+            let method = format_ident!("{}_mut", method);
+            let builder_ty = &sub_builder.builder_ty;
+            return quote! {
+                #vis fn #method(&mut self) -> &mut #builder_ty {
+                    &mut self.#name
+                }
+            };
+        }
+        let mut methods = Vec::new();
+        if let Some(each) = &f.each {
+            let (params, item) = each.setter_args(into);
+            let collection = f.collection(quote! { __builder.#name });
+            methods.push(setter(
+                vis,
+                &each.method,
+                params,
+                f.collection_bound(),
+                quote! {
+                    core::iter::Extend::extend(#collection, core::iter::once(#item))
+                },
+            ));
+            let item_ty = each.item_type();
+            methods.push(setter(
+                vis,
+                &format_ident!("extend_{}", method),
+                quote! { #name: impl core::iter::IntoIterator<Item = #item_ty> },
+                f.collection_bound(),
+                quote! { core::iter::Extend::extend(#collection, #name) },
+            ));
+            // The setter for the whole collection would clash with a per-item
+            // setter of the same name.
+            if each.method == *method {
+                return quote! { #(#methods)* };
+            }
+        }
+        let setter_ty = f.setter_type(strip_option);
+        let (arg_ty, value) = setter_arg(setter_ty, name, into);
+        let value = f.setter_value(strip_option, value);
+        methods.push(setter(
+            vis,
+            method,
+            quote! { #name: #arg_ty },
+            None,
+            quote! { __builder.#name = #value },
+        ));
+        // The `try_` setter converts its argument and passes it on to the
+        // setter above.
+        if f.has_try_setter(container_attribute) {
+            let try_method = format_ident!("try_{}", method);
+            let (receiver, output) = match pattern {
+                Pattern::Owned => (quote! { self }, quote! { Self }),
+                Pattern::Mutable => (quote! { &mut self }, quote! { &mut Self }),
+                Pattern::Immutable => (quote! { &self }, quote! { Self }),
+            };
+            methods.push(quote! {
+                #vis fn #try_method<__V: core::convert::TryInto<#setter_ty>>(
+                    #receiver,
+                    #name: __V,
+                ) -> core::result::Result<#output, __V::Error> {
+                    let value = core::convert::TryInto::try_into(#name)?;
+                    core::result::Result::Ok(self.#method(value))
+                }
+            });
+        }
+        quote! { #(#methods)* }
+    });
+
+    // With `build_fn(error)`, the generated error enum is still emitted for
+    // other builders to refer to, but `build` converts into the user's type
+    // through the companion crate's error types.
+    let custom_error = container_attribute.build_fn.error.as_ref();
+    let build_error = match custom_error {
+        Some(error) => quote! { #error },
+        None => quote! { #error_type_name },
+    };
+    let missing_error = |fields: TokenStream2| match custom_error {
+        Some(_) => quote! {
+            core::convert::From::from(::derive_builder::UninitializedFieldError::new(#fields))
+        },
+        None => quote! { #error_type_name::UninitializedFields(#fields) },
+    };
+    let validation_error = |message: TokenStream2| match custom_error {
+        Some(_) => quote! {
+            core::convert::From::from(::derive_builder::ValidationError::new(#message))
+        },
+        None => quote! { #error_type_name::Validation(#message) },
+    };
+
+    // A sub-builder's missing fields are reported together with the outer
+    // ones, prefixed with the field's name.
+    let field_name_check = fields.iter().map(|f| {
+        let name = &f.name;
+        let name_str = name.unraw().to_string();
+        if f.sub_builder.is_some() {
+            let prefix = format!("{}.", name_str);
+            quote_spanned! {f.ty.span()=>
+                ::derive_builder::__private::SubBuilder::collect_missing(
+                    &self.#name,
+                    &#alloc::format!("{}{}", __prefix, #prefix),
+                    __missing,
+                );
+            }
+        } else if f.is_required() {
+            quote! {
+                if self.#name.is_none() {
+                    __missing.push(#alloc::format!("{}{}", __prefix, #name_str));
+                }
+            }
+        } else {
+            quote! {}
+        }
+    });
+
+    // The owned pattern consumes the builder and can move each value into the
+    // target; the other patterns only borrow it and have to clone. Once no
+    // field is missing, a sub-builder can only fail its own validation.
+    let field_name_build = fields.iter().map(|f| {
+        let name = &f.name;
+        let member = &f.member;
+        if f.sub_builder.is_some() {
+            let built = match pattern {
+                Pattern::Owned => quote_spanned! {f.ty.span()=>
+                    ::derive_builder::__private::SubBuilder::build_owned(self.#name)
+                },
+                Pattern::Mutable | Pattern::Immutable => quote_spanned! {f.ty.span()=>
+                    ::derive_builder::__private::SubBuilderRef::build_ref(&self.#name)
+                },
+            };
+            let context = format!("{}: {{}}", name.unraw());
+            let error = validation_error(quote! { #alloc::format!(#context, err) });
+            return quote_spanned! {f.ty.span()=>
+                #member: match #built {
+                    core::result::Result::Ok(value) => value,
+                    core::result::Result::Err(err) => return core::result::Result::Err(#error),
+                },
+            };
+        }
+        let value = match pattern {
+            Pattern::Owned => quote! { self.#name },
+            Pattern::Mutable | Pattern::Immutable => {
+                quote_spanned! {f.ty.span()=>
+                    core::clone::Clone::clone(&self.#name)
+                }
+            }
+        };
+        if let (true, Some(fallback)) = (f.is_tracked(), f.fallback()) {
+            quote! {
+                #member: match #value {
+                    core::option::Option::Some(value) => value,
+                    core::option::Option::None => #fallback,
+                },
+            }
+        } else if f.is_tracked() {
+            quote! {
+                #member: core::option::Option::unwrap(#value),
+            }
+        } else {
+            quote! {
+                #member: #value,
+            }
+        }
+    });
+    let build_receiver = match pattern {
+        Pattern::Owned => quote! { self },
+        Pattern::Mutable | Pattern::Immutable => quote! { &self },
+    };
+    let clone_types = match pattern {
+        Pattern::Owned => Vec::new(),
+        Pattern::Mutable | Pattern::Immutable => field_types,
+    };
+    // Immutable setters return a modified copy of the builder.
+    let mut derives: Vec<_> = container_attribute.derive.iter().flatten().collect();
+    let clone: Path = syn::parse_quote!(Clone);
+    if pattern == Pattern::Immutable && !derives.iter().any(|path| path.is_ident("Clone")) {
+        derives.push(&clone);
+    }
+    let builder_derives = (!derives.is_empty()).then(|| quote! { #[derive(#(#derives),*)] });
+
+    // Validation sees the builder with every required field set and runs
+    // before any of them are moved out of it. A custom error type takes the
+    // validator's error as it is, while the generated enum keeps its message.
+    let validate = container_attribute
+        .build_fn
+        .validate
+        .as_ref()
+        .map(|validate| {
+            let error = match custom_error {
+                Some(_) => quote_spanned! {validate.span()=> core::convert::From::from(err) },
+                None => validation_error(quote! { #alloc::string::ToString::to_string(&err) }),
+            };
+            quote! {
+                if let core::result::Result::Err(err) = #validate(&self) {
+                    return core::result::Result::Err(#error);
+                }
+            }
+        });
+    let missing_error = missing_error(quote! { __missing });
+
+    let introspection = target.introspection(&alloc, |f| f.is_set());
+
+    let error_doc = format!("Error returned by [`{}::{}`].", builder_type_name, build_fn);
+
+    // A variant's builder need not mention every parameter of the enum.
+    let (phantom_field, phantom_init) = if generics.params.is_empty() {
+        (None, None)
+    } else {
+        (
+            Some(quote! { __phantom: core::marker::PhantomData<fn() -> #type_name #ty_generics>, }),
+            Some(quote! { __phantom: core::marker::PhantomData, }),
+        )
+    };
+
+    // With `serde`, every field of the builder may be missing from the input
+    // and keeps the value of a new builder. Layers deserialized separately are
+    // then combined with `merge`, where a field set in `other` wins and
+    // collections are appended to.
+    let (serde_derive, serde_skip, serde_items) = if container_attribute.serde.is_some() {
+        let field_name_merge = fields.iter().map(|f| {
+            let name = &f.name;
+            if f.sub_builder.is_some() {
+                quote! { self.#name.merge(other.#name); }
+            } else if f.each.is_some() {
+                // A collection that was never touched takes the items of
+                // `other` in place of its default.
+                quote! {
+                    if let core::option::Option::Some(items) = other.#name {
+                        match &mut self.#name {
+                            core::option::Option::Some(current) => core::iter::Extend::extend(current, items),
+                            core::option::Option::None => self.#name = core::option::Option::Some(items),
+                        }
+                    }
+                }
+            } else {
+                quote! {
+                    if core::option::Option::is_some(&other.#name) {
+                        self.#name = other.#name;
+                    }
+                }
+            }
+        });
+        (
+            Some(quote! {
+                #[derive(serde::Deserialize)]
+                #[serde(default)]
+            }),
+            phantom_field.as_ref().map(|_| quote! { #[serde(skip)] }),
+            Some(quote! {
+                impl #impl_generics core::default::Default for #builder_type_name #ty_generics #where_clause {
+                    fn default() -> Self {
+                        <#type_name #ty_generics>::#builder_fn()
+                    }
+                }
+
+                impl #impl_generics #builder_type_name #ty_generics #where_clause {
+                    /// Overwrites every field that is set in `other`, and
+                    /// appends the items of its collections.
+                    #vis fn merge(&mut self, other: Self) {
+                        #(#field_name_merge)*
+                    }
+                }
+            }),
+        )
+    } else {
+        (None, None, None)
+    };
+
+    let items = quote! {
+        #builder_derives
+        #serde_derive
+        #vis struct #builder_type_name #generics #where_clause {
+            #(#field_names: #field_storage_types,)*
+            #serde_skip
+            #phantom_field
+        }
+
+        #serde_items
+
+        #[doc = #error_doc]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis enum #error_type_name {
+            /// Every required field that was never set, in declaration order.
+            UninitializedFields(#alloc::vec::Vec<#alloc::string::String>),
+            /// The error returned by the `build_fn(validate)` function, kept
+            /// as its Display output, or that of a sub-builder's `build`.
+            Validation(#alloc::string::String),
+        }
+
+        impl core::fmt::Display for #error_type_name {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                match self {
+                    #error_type_name::UninitializedFields(fields) => {
+                        f.write_str("missing required fields: ")?;
+                        for (i, field) in fields.iter().enumerate() {
+                            if i > 0 {
+                                f.write_str(", ")?;
+                            }
+                            core::write!(f, "`{}`", field)?;
+                        }
+                        core::result::Result::Ok(())
+                    }
+                    #error_type_name::Validation(message) => f.write_str(message),
+                }
+            }
+        }
+
+        impl #error_trait for #error_type_name {}
+
+        impl #impl_generics #builder_type_name #ty_generics #where_clause {
+            #(#field_name_method)*
+
+            #introspection
+
+            #[doc(hidden)]
+            #vis fn __collect_missing(
+                &self,
+                __prefix: &str,
+                __missing: &mut #alloc::vec::Vec<#alloc::string::String>,
+            ) {
+                #(#field_name_check)*
+            }
+
+                #vis fn #build_fn(#build_receiver) -> core::result::Result<#type_name #ty_generics, #build_error>
+                where
+                    #(#clone_types: core::clone::Clone,)*
+                    #(#default_types: core::default::Default,)*
+                    #(#trait_default_types: core::default::Default,)*
+                {
+                    let mut __missing = #alloc::vec::Vec::new();
+                    self.__collect_missing("", &mut __missing);
+                    if !__missing.is_empty() {
+                        return core::result::Result::Err(#missing_error);
+                    }
+                    #validate
+                    core::result::Result::Ok(#constructor {
+                        #(#field_name_build)*
+                        #(#skipped_field_build,)*
+                    })
+                }
+        }
+    };
+
+    // The traits name the builder as the type's `Builder`, which a builder
+    // made less visible than its type with `vis` cannot be.
+    let nestable = matches!(ast.data, Data::Struct(_))
+        && constructor_params.is_empty()
+        && (container_attribute.vis.is_none() || matches!(vis, Visibility::Public(_)));
+    let sub_builder_impls = nestable.then(|| {
+        let mut sub_builder_generics = generics.clone();
+        let sub_builder_where = sub_builder_generics.make_where_clause();
+        for ty in default_types.iter().chain(&trait_default_types) {
+            sub_builder_where
+                .predicates
+                .push(syn::parse_quote!(#ty: core::default::Default));
+        }
+        for ty in &clone_types {
+            sub_builder_where
+                .predicates
+                .push(syn::parse_quote!(#ty: core::clone::Clone));
+        }
+        let (_, _, sub_builder_where_clause) = sub_builder_generics.split_for_impl();
+        let private = quote! { ::derive_builder::__private };
+        let builder_ty = quote! { #builder_type_name #ty_generics };
+        let target_ty = quote! { #type_name #ty_generics };
+        let result_ty = quote! { core::result::Result<#target_ty, #build_error> };
+        let build_ref = match pattern {
+            Pattern::Owned => None,
+            Pattern::Mutable | Pattern::Immutable => Some(quote! {
+                impl #impl_generics #private::SubBuilderRef for #builder_ty #sub_builder_where_clause {
+                    fn build_ref(&self) -> #result_ty {
+                        self.#build_fn()
+                    }
+                }
+            }),
+        };
+        let build_from = container_attribute.to_builder.as_ref().map(|_| {
+            quote! {
+                impl #impl_generics #private::SubBuilderFrom for #builder_ty #sub_builder_where_clause {
+                    fn from_target(target: #target_ty) -> Self {
+                        core::convert::From::from(target)
+                    }
+                }
+            }
+        });
+        let build_owned = match pattern {
+            Pattern::Owned => quote! { self.#build_fn() },
+            Pattern::Mutable | Pattern::Immutable => quote! { (&self).#build_fn() },
+        };
+        quote! {
+            impl #impl_generics #private::Buildable for #target_ty #sub_builder_where_clause {
+                type Builder = #builder_ty;
+            }
+
+            impl #impl_generics #private::SubBuilder for #builder_ty #sub_builder_where_clause {
+                type Target = #target_ty;
+                type Error = #build_error;
+
+                fn new_builder() -> Self {
+                    <#target_ty>::#builder_fn()
+                }
+
+                fn collect_missing(
+                    &self,
+                    prefix: &str,
+                    missing: &mut #alloc::vec::Vec<#alloc::string::String>,
+                ) {
+                    self.__collect_missing(prefix, missing)
+                }
+
+                fn build_owned(self) -> #result_ty {
+                    #build_owned
+                }
+            }
+
+            #build_ref
+            #build_from
+        }
+    });
+
+    let builder_fn = quote! {
+        #vis fn #builder_fn(#(#constructor_params),*) -> #builder_type_name #ty_generics {
+            #builder_type_name {
+                #(#field_name_init,)*
+                #(#constructor_init,)*
+                #phantom_init
+            }
+        }
+    };
+
+    let (from_impl, to_builder) = match &container_attribute.to_builder {
+        Some(_) => {
+            let (from_impl, to_builder) = target.to_builder(
+                ast,
+                quote! { #builder_type_name #ty_generics },
+                |f, value| {
+                    if let Some(sub_builder) = &f.sub_builder {
+                        let builder_ty = &sub_builder.builder_ty;
+                        quote_spanned! {f.ty.span()=>
+                            <#builder_ty as ::derive_builder::__private::SubBuilderFrom>::from_target(#value)
+                        }
+                    } else if f.is_tracked() {
+                        quote! { core::option::Option::Some(#value) }
+                    } else {
+                        value
+                    }
+                },
+                phantom_init,
+            );
+            (Some(from_impl), Some(to_builder))
+        }
+        None => (None, None),
+    };
+
+    let env_impl = env::impl_env(ast, target, container_attribute);
+
+    BuilderOutput {
+        items: quote! {
+            #items
+            #sub_builder_impls
+            #from_impl
+            #env_impl
+        },
+        builder_fn: quote! {
+            #builder_fn
+            #to_builder
+        },
+    }
+}
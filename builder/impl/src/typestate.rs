@@ -0,0 +1,392 @@
+use crate::{
+    setter_arg, to_snake_case, BuilderField, BuilderOutput, BuilderTarget, ContainerAttribute,
+};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{DeriveInput, GenericParam, Ident, Type};
+
+// In typestate mode every required field gets its own type parameter on the
+// builder. The parameter starts out as `Unset` and its setter turns it into
+// `Set<T>`, so `build` can demand through its where-clause that every required
+// parameter is `Set`. There is nothing left to check at runtime.
+//
+//     pub struct MessageBuilder<__S0 = Unset, __S1 = Unset> {
+//         id: __S0,
+//         payload: __S1,
+//         tags: Vec<String>,
+//     }
+//
+// The marker types live in a hidden module next to the builder together with
+// one trait per required field. The traits exist only so that a forgotten
+// setter is reported in terms of the field name.
+pub(crate) fn impl_typestate_builder(
+    ast: &DeriveInput,
+    target: &BuilderTarget,
+    container_attribute: &ContainerAttribute,
+) -> BuilderOutput {
+    let type_name = &ast.ident;
+    let builder_type_name = &target.builder_type_name;
+    let constructor = &target.constructor;
+    let builder_fn = &target.builder_fn;
+    let build_fn = &target.build_fn;
+    let vis = &target.vis;
+    let fields = &target.fields;
+    let state_mod = format_ident!("__{}_state", to_snake_case(builder_type_name));
+
+    let generics = &ast.generics;
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+
+    let required: Vec<&BuilderField> = fields.iter().filter(|f| f.is_required()).collect();
+    let state_params: Vec<Ident> = (0..required.len())
+        .map(|i| format_ident!("__S{}", i))
+        .collect();
+
+    // The type parameter of each required field, if it has one.
+    let state_param = |field: &BuilderField| {
+        required
+            .iter()
+            .position(|f| f.name == field.name)
+            .map(|i| &state_params[i])
+    };
+
+    let mut builder_generics = generics.clone();
+    builder_generics.params.extend(
+        state_params
+            .iter()
+            .map(|p| -> GenericParam { syn::parse_quote!(#p = #state_mod::Unset) }),
+    );
+    let mut state_generics = generics.clone();
+    state_generics.params.extend(
+        state_params
+            .iter()
+            .map(|p| -> GenericParam { syn::parse_quote!(#p) }),
+    );
+    let (state_impl_generics, _, _) = state_generics.split_for_impl();
+
+    let user_params: Vec<_> = generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                quote! { #lifetime }
+            }
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                quote! { #ident }
+            }
+            GenericParam::Const(param) => {
+                let ident = &param.ident;
+                quote! { #ident }
+            }
+        })
+        .collect();
+
+    let field_names: Vec<_> = fields.iter().map(|f| &f.name).collect();
+    let field_storage_types = fields.iter().map(|f| {
+        let ty = &f.ty;
+        match state_param(f) {
+            Some(param) => quote! { #param },
+            None if f.is_tracked() => quote! { core::option::Option<#ty> },
+            None => quote! { #ty },
+        }
+    });
+
+    let (constructor_params, constructor_init) = target.constructor_args(container_attribute);
+    let field_name_init = fields
+        .iter()
+        .filter(|f| f.attr.constructor_arg.is_none())
+        .map(|f| {
+            let name = &f.name;
+            if f.is_required() {
+                quote! { #name: #state_mod::Unset }
+            } else {
+                quote! { #name: core::option::Option::None }
+            }
+        });
+
+    let field_name_method = fields.iter().map(|f| {
+        let name = &f.name;
+        let ty = &f.ty;
+        let (into, strip_option) = f.setter_flags(container_attribute);
+        let setter = f.setter_name();
+        let setter_vis = &f.setter_vis(container_attribute, target);
+        // The `try_` setter converts its argument and passes it on to the
+        // setter, returning whatever the setter returns.
+        let try_setter = |setter_ty: &Type, output: &TokenStream2| {
+            if !f.has_try_setter(container_attribute) {
+                return None;
+            }
+            let try_setter = format_ident!("try_{}", setter);
+            Some(quote! {
+                #setter_vis fn #try_setter<__V: core::convert::TryInto<#setter_ty>>(
+                    self,
+                    #name: __V,
+                ) -> core::result::Result<#output, __V::Error> {
+                    let value = core::convert::TryInto::try_into(#name)?;
+                    core::result::Result::Ok(self.#setter(value))
+                }
+            })
+        };
+
+        if let Some(param) = state_param(f) {
+            // Replace this field's state parameter with `Set<T>` and move every
+            // other field over unchanged.
+            let output_params = state_params.iter().map(|p| {
+                if p == param {
+                    quote! { #state_mod::Set<#ty> }
+                } else {
+                    quote! { #p }
+                }
+            });
+            let output = quote! {
+                #builder_type_name<#(#user_params,)* #(#output_params),*>
+            };
+            let (arg_ty, value) = setter_arg(ty, name, into);
+            let moved = field_names.iter().map(|other| {
+                if *other == name {
+                    quote! { #name: #state_mod::Set(#value) }
+                } else {
+                    quote! { #other: self.#other }
+                }
+            });
+            let try_setter = try_setter(ty, &output);
+            return quote! {
+                #setter_vis fn #setter(self, #name: #arg_ty) -> #output {
+                    #builder_type_name {
+                        #(#moved,)*
+                        __phantom: core::marker::PhantomData,
+                    }
+                }
+
+                #try_setter
+            };
+        }
+
+        let mut methods = Vec::new();
+        if let Some(each) = &f.each {
+            let method = &each.method;
+            let (params, item) = each.setter_args(into);
+            let extend = format_ident!("extend_{}", setter);
+            let item_ty = each.item_type();
+            let collection = f.collection(quote! { self.#name });
+            let bound = f.collection_bound();
+            methods.push(quote! {
+                #setter_vis fn #method(mut self, #params) -> Self #bound {
+                    core::iter::Extend::extend(#collection, core::iter::once(#item));
+                    self
+                }
+
+                #setter_vis fn #extend(mut self, #name: impl core::iter::IntoIterator<Item = #item_ty>) -> Self #bound {
+                    core::iter::Extend::extend(#collection, #name);
+                    self
+                }
+            });
+            if method == setter {
+                return quote! { #(#methods)* };
+            }
+        }
+        let setter_ty = f.setter_type(strip_option);
+        let (arg_ty, value) = setter_arg(setter_ty, name, into);
+        let value = f.setter_value(strip_option, value);
+        methods.push(quote! {
+            #setter_vis fn #setter(mut self, #name: #arg_ty) -> Self {
+                self.#name = #value;
+                self
+            }
+        });
+        methods.extend(try_setter(setter_ty, &quote! { Self }));
+        quote! { #(#methods)* }
+    });
+
+    let field_state_traits: Vec<_> = required.iter().map(|f| &f.name).collect();
+
+    let build_bounds = required.iter().zip(&state_params).map(|(f, param)| {
+        let name = &f.name;
+        let ty = &f.ty;
+        quote! { #param: #state_mod::#name<#ty> }
+    });
+
+    // A required field is moved out through its state parameter, which the
+    // build bounds above guarantee to be `Set`.
+    let field_name_build = fields.iter().map(|f| {
+        let name = &f.name;
+        let member = &f.member;
+        match state_param(f) {
+            Some(param) => {
+                let ty = &f.ty;
+                quote! { #member: <#param as #state_mod::#name<#ty>>::into_value(self.#name) }
+            }
+            None => match (f.is_tracked(), f.fallback()) {
+                (true, Some(fallback)) => {
+                    quote! {
+                        #member: match self.#name {
+                            core::option::Option::Some(value) => value,
+                            core::option::Option::None => #fallback,
+                        }
+                    }
+                }
+                _ => quote! { #member: self.#name },
+            },
+        }
+    });
+
+    let trait_default_types = target.trait_default_types();
+    let skipped_field_build = target.skipped_field_build();
+    let default_types = target.collection_types();
+
+    let unset_state_params = state_params.iter().map(|_| quote! { #state_mod::Unset });
+
+    let messages = field_state_traits.iter().map(|name| {
+        format!(
+            "`{}::{}` cannot be called before `{}` is set",
+            builder_type_name, build_fn, name
+        )
+    });
+    let labels = field_state_traits
+        .iter()
+        .map(|name| format!("`{}` has not been set", name));
+    let notes = required
+        .iter()
+        .map(|f| format!("call `.{}(...)` on the builder first", f.setter_name()));
+
+    // A required field is set exactly when its state parameter is `Set`, which
+    // the introspection methods read off the type.
+    let alloc = match container_attribute.no_std {
+        Some(_) => quote! { alloc },
+        None => quote! { std },
+    };
+    let introspection = target.introspection(&alloc, |f| match state_param(f) {
+        Some(param) => quote! { <#param as #state_mod::FieldState>::IS_SET },
+        None => f.is_set(),
+    });
+    let mut introspection_generics = state_generics.clone();
+    introspection_generics
+        .make_where_clause()
+        .predicates
+        .extend(
+            state_params
+                .iter()
+                .map(|p| -> syn::WherePredicate { syn::parse_quote!(#p: #state_mod::FieldState) }),
+        );
+    let (_, _, introspection_where_clause) = introspection_generics.split_for_impl();
+
+    let state_mod_doc = format!("Typestate markers for [`{}`].", builder_type_name);
+
+    let items = quote! {
+        #[doc = #state_mod_doc]
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        pub mod #state_mod {
+            /// A required field that has not been set yet.
+            pub struct Unset;
+
+            /// A required field that has been set to the wrapped value.
+            pub struct Set<T>(pub T);
+
+            /// Tells the introspection methods of the builder whether a
+            /// required field is set.
+            pub trait FieldState {
+                const IS_SET: bool;
+            }
+
+            impl FieldState for Unset {
+                const IS_SET: bool = false;
+            }
+
+            impl<T> FieldState for Set<T> {
+                const IS_SET: bool = true;
+            }
+
+            #(
+                #[diagnostic::on_unimplemented(message = #messages, label = #labels, note = #notes)]
+                pub trait #field_state_traits<T> {
+                    fn into_value(self) -> T;
+                }
+
+                impl<T> #field_state_traits<T> for Set<T> {
+                    fn into_value(self) -> T {
+                        self.0
+                    }
+                }
+            )*
+        }
+
+        // The types of the required fields appear only in the state
+        // parameters, and a variant's builder need not mention every
+        // parameter of the enum, so the derived type's own generic parameters
+        // are kept alive through a marker.
+        #vis struct #builder_type_name #builder_generics #where_clause {
+            #(#field_names: #field_storage_types,)*
+            __phantom: core::marker::PhantomData<fn() -> #type_name #ty_generics>,
+        }
+
+        impl #state_impl_generics #builder_type_name<#(#user_params,)* #(#state_params),*> #where_clause {
+            #(#field_name_method)*
+
+            #vis fn #build_fn(self) -> #type_name #ty_generics
+            where
+                #(#build_bounds,)*
+                #(#default_types: core::default::Default,)*
+                #(#trait_default_types: core::default::Default,)*
+            {
+                #constructor {
+                    #(#field_name_build,)*
+                    #(#skipped_field_build,)*
+                }
+            }
+        }
+
+        impl #state_impl_generics #builder_type_name<#(#user_params,)* #(#state_params),*> #introspection_where_clause {
+            #introspection
+        }
+    };
+
+    let builder_fn = quote! {
+        #vis fn #builder_fn(#(#constructor_params),*) -> #builder_type_name<#(#user_params,)* #(#unset_state_params),*> {
+            #builder_type_name {
+                #(#field_name_init,)*
+                #(#constructor_init,)*
+                __phantom: core::marker::PhantomData,
+            }
+        }
+    };
+
+    // A builder made from an existing value starts out with every required
+    // field set.
+    let (from_impl, to_builder) = match &container_attribute.to_builder {
+        Some(_) => {
+            let set_state_params = required.iter().map(|f| {
+                let ty = &f.ty;
+                quote! { #state_mod::Set<#ty> }
+            });
+            let (from_impl, to_builder) = target.to_builder(
+                ast,
+                quote! { #builder_type_name<#(#user_params,)* #(#set_state_params),*> },
+                |f, value| {
+                    if f.is_required() {
+                        quote! { #state_mod::Set(#value) }
+                    } else if f.is_tracked() {
+                        quote! { core::option::Option::Some(#value) }
+                    } else {
+                        value
+                    }
+                },
+                Some(quote! { __phantom: core::marker::PhantomData, }),
+            );
+            (Some(from_impl), Some(to_builder))
+        }
+        None => (None, None),
+    };
+
+    BuilderOutput {
+        items: quote! {
+            #items
+            #from_impl
+        },
+        builder_fn: quote! {
+            #builder_fn
+            #to_builder
+        },
+    }
+}
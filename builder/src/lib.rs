@@ -1,161 +1,236 @@
-use proc_macro::TokenStream;
-use proc_macro2::Span;
-use quote::{format_ident, quote};
-use syn::{
-    Attribute, Data, DataStruct, DeriveInput, Expr, ExprAssign, ExprLit, Fields, FieldsNamed,
-    GenericArgument, Ident, Lit, Meta, Path, PathArguments, PathSegment, Type, TypePath,
-};
-
-#[proc_macro_derive(Builder, attributes(builder))]
-pub fn derive(input: TokenStream) -> TokenStream {
-    let ast = syn::parse(input).unwrap();
-    impl_builder(&ast)
+// Crates with the "proc-macro" crate type can only export procedural macros,
+// but the generated builders refer to some ordinary types: the errors that a
+// custom error type converts from, and the descriptions of their fields. The
+// Builder derive therefore lives in the derive_builder_impl crate and is
+// re-exported here next to those types, so that users only import this one
+// crate.
+//
+// This crate does not need the standard library, so that it can be used
+// together with `#[builder(no_std)]`.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+
+pub use derive_builder_impl::Builder;
+
+/// Returned by `build` when required fields were never set, and converted
+/// into the error type given with `build_fn(error = "...")`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UninitializedFieldError {
+    fields: Vec<String>,
 }
 
-fn inner_type<'a>(wrapper: &str, ty: &'a Type) -> Option<&'a Type> {
-    if let Type::Path(TypePath {
-        path: Path { ref segments, .. },
-        ..
-    }) = ty
-    {
-        if let Some(PathSegment { ident, arguments }) = segments.first() {
-            if ident == wrapper {
-                if let PathArguments::AngleBracketed(arg) = arguments {
-                    if let Some(GenericArgument::Type(typ)) = arg.args.first() {
-                        return Some(typ);
-                    }
-                }
+impl UninitializedFieldError {
+    pub fn new(fields: Vec<String>) -> Self {
+        UninitializedFieldError { fields }
+    }
+
+    /// Every required field that was never set, in declaration order. Fields
+    /// of a sub-builder are given by their path, as in `listen.port`.
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+}
+
+impl Display for UninitializedFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("missing required fields: ")?;
+        for (i, field) in self.fields.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
             }
+            write!(f, "`{}`", field)?;
         }
+        Ok(())
     }
-    None
 }
 
-#[derive(Default)]
-struct FieldAttribute {
-    each_name: Option<Ident>,
+impl core::error::Error for UninitializedFieldError {}
+
+/// Returned by `build` when the `build_fn(validate = ...)` function of a
+/// sub-builder fails, with the field's name in front of the message, and
+/// converted into the error type given with `build_fn(error = "...")`. A
+/// builder's own validation error is converted into that type unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    message: String,
 }
 
-fn attr_builder_value(attrs: &Vec<Attribute>) -> Result<Option<FieldAttribute>, syn::Error> {
-    if attrs.len() == 0 {
-        return Ok(None);
-    }
-    let mut field_attribute = FieldAttribute::default();
-    for attr in attrs.iter().filter(|attr| attr.path().is_ident("builder")) {
-        if let Meta::List(ref meta) = attr.meta {
-            if let Ok(Expr::Assign(ExprAssign { left, right, .. })) = attr.parse_args() {
-                if let Expr::Path(expr) = *left {
-                    if let Some(ident) = expr.path.get_ident() {
-                        match ident {
-                            _ if ident == "each" => {
-                                if let Expr::Lit(ExprLit {
-                                    lit: Lit::Str(lit_str),
-                                    ..
-                                }) = *right
-                                {
-                                    field_attribute.each_name =
-                                        Some(Ident::new(&lit_str.value(), Span::call_site()));
-                                }
-                            }
-                            _ => {
-                                return Err(syn::Error::new_spanned(
-                                    meta,
-                                    r#"expected `builder(each = "...")`"#,
-                                ));
-                            }
-                        }
-                    }
-                }
-            }
-        }
+impl ValidationError {
+    pub fn new(message: String) -> Self {
+        ValidationError { message }
+    }
+
+    /// The message of the error returned by the sub-builder's validation.
+    pub fn message(&self) -> &str {
+        &self.message
     }
-    Ok(Some(field_attribute))
 }
 
-fn impl_builder(ast: &DeriveInput) -> TokenStream {
-    //eprintln!("{:#?}", ast);
-    let type_name = &ast.ident;
-    let builder_type_name = format_ident!("{}Builder", type_name);
-
-    let fields = match ast.data {
-        Data::Struct(DataStruct {
-            fields: Fields::Named(FieldsNamed { ref named, .. }),
-            ..
-        }) => named,
-        _ => panic!("malformed type"),
-    };
-
-    let field_names: Vec<_> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
-    let field_types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
-
-    let field_name_method = fields.iter().map(|f| {
-        let name = f.ident.as_ref().unwrap();
-        let ty = &f.ty;
-
-        if let Some(ty) = inner_type("Option", ty) {
-            return quote! {
-                fn #name(&mut self, #name: #ty) -> &mut Self {
-                    self.#name = core::option::Option::Some(#name);
-                    self
-                }
-            };
-        } else {
-            match attr_builder_value(&f.attrs) {
-                Ok(attr) => {
-                    if let Some(attr) = attr {
-                        if let Some(each_name) = attr.each_name {
-                            let ty = inner_type("Vec", ty).unwrap();
-                            return quote! {
-                                fn #each_name(&mut self, #each_name: #ty) -> &mut Self {
-                                    self.#name.push(#each_name);
-                                    self
-                                }
-                            };
-                        }
-                    }
-                }
-                Err(err) => {
-                    return err.to_compile_error();
-                }
-            }
-        }
-        quote! {
-            fn #name(&mut self, #name: #ty) -> &mut Self {
-                self.#name = #name;
-                self
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl core::error::Error for ValidationError {}
+
+/// Describes one field of a builder, as listed in its `FIELDS` constant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldInfo {
+    /// The name of the field, which is also the name of its setter unless
+    /// renamed with `setter(name = "...")`.
+    pub name: &'static str,
+    /// The type of the field as written in the derived type.
+    pub ty: &'static str,
+    pub kind: FieldKind,
+}
+
+/// Whether a field has to be set before `build`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    /// The field has to be set before `build`.
+    Required,
+    /// The field is an `Option` or has a default.
+    Optional,
+    /// The field is a collection filled item by item through `each`.
+    Each,
+    /// The field is passed to the builder function, so it is always set.
+    ConstructorArg,
+    /// The field is built by a nested builder, which may have required fields
+    /// of its own.
+    SubBuilder,
+}
+
+/// Returned by `from_env` and `from_map` when some variables could not be
+/// parsed into their fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvError {
+    errors: Vec<EnvFieldError>,
+}
+
+impl EnvError {
+    pub fn new(errors: Vec<EnvFieldError>) -> Self {
+        EnvError { errors }
+    }
+
+    /// One error for every variable that could not be parsed, in the order of
+    /// the fields.
+    pub fn errors(&self) -> &[EnvFieldError] {
+        &self.errors
+    }
+}
+
+impl Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, error) in self.errors.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
             }
+            write!(f, "{}", error)?;
         }
-    });
+        Ok(())
+    }
+}
 
-    let field_name_build = fields.iter().map(|f| {
-        let name = f.ident.as_ref().unwrap();
-        quote! {
-            #name: self.#name.clone(),
-        }
-    });
+impl core::error::Error for EnvError {}
 
-    let gen = quote! {
-        pub struct #builder_type_name {
-            #(#field_names: #field_types,)*
+/// A variable that could not be parsed into the field it sets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvFieldError {
+    field: &'static str,
+    var: &'static str,
+    message: String,
+}
+
+impl EnvFieldError {
+    pub fn new(field: &'static str, var: &'static str, message: String) -> Self {
+        EnvFieldError {
+            field,
+            var,
+            message,
         }
+    }
 
-        impl #builder_type_name {
-            #(#field_name_method)*
+    pub fn field(&self) -> &'static str {
+        self.field
+    }
 
-                pub fn build(&mut self) -> core::result::Result<#type_name, std::boxed::Box<dyn std::error::Error>> {
-                    core::result::Result::Ok(#type_name{
-                        #(#field_name_build)*
-                    })
-                }
-        }
+    pub fn var(&self) -> &'static str {
+        self.var
+    }
 
-        impl #type_name {
-            pub fn builder() -> #builder_type_name {
-                #builder_type_name {
-                    #(#field_names: <#field_types>::default()),*
-                }
-            }
+    /// The error message of the value's `FromStr` implementation.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for EnvFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid `{}` for field `{}`: {}",
+            self.var, self.field, self.message
+        )
+    }
+}
+
+impl core::error::Error for EnvFieldError {}
+
+// The interface through which a builder builds the builder of a field marked
+// `#[builder(sub_builder)]`. It goes through the inner builder's own `build`,
+// whatever it is named, whichever error it returns and whether or not it takes
+// the builder by value.
+#[doc(hidden)]
+pub mod __private {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    #[diagnostic::on_unimplemented(
+        message = "`{Self}` cannot be used with `sub_builder`",
+        note = "the type of a `sub_builder` field must derive `Builder` on a struct without `typestate` or `constructor_arg` fields, and without a less visible `vis` for its builder"
+    )]
+    pub trait Buildable {
+        type Builder: SubBuilder<Target = Self>;
+    }
+
+    pub trait SubBuilder: Sized {
+        type Target;
+        type Error;
+
+        fn new_builder() -> Self;
+
+        /// Appends the path of every required field that is not set, those of
+        /// nested sub-builders included, each preceded by `prefix`.
+        fn collect_missing(&self, prefix: &str, missing: &mut Vec<String>);
+
+        fn is_complete(&self) -> bool {
+            let mut missing = Vec::new();
+            self.collect_missing("", &mut missing);
+            missing.is_empty()
         }
-    };
-    TokenStream::from(gen)
+
+        fn build_owned(self) -> Result<Self::Target, Self::Error>;
+    }
+
+    #[diagnostic::on_unimplemented(
+        message = "`{Self}` uses the owned pattern, so it cannot be built from a reference",
+        note = "a sub-builder using the owned pattern can only be nested in a builder using the owned pattern too"
+    )]
+    pub trait SubBuilderRef: SubBuilder {
+        fn build_ref(&self) -> Result<Self::Target, Self::Error>;
+    }
+
+    #[diagnostic::on_unimplemented(
+        message = "`{Self}` cannot be made from an existing value",
+        note = "a sub-builder in a builder with `to_builder` needs `to_builder` on its own type too"
+    )]
+    pub trait SubBuilderFrom: SubBuilder {
+        fn from_target(target: Self::Target) -> Self;
+    }
 }
@@ -0,0 +1,69 @@
+// The builder remembers which required fields have been set, and `build`
+// reports every one that is still missing in a single error instead of
+// handing back a struct full of default values.
+//
+// The error is a generated enum named after the builder. It implements
+// Display and std::error::Error so it can be propagated with `?` into a
+// Box<dyn Error>.
+//
+//     pub enum CommandBuilderError {
+//         UninitializedFields(Vec<String>),
+//     }
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    env: Vec<String>,
+    current_dir: Option<String>,
+    timeout: u64,
+}
+
+#[derive(Builder, Debug)]
+pub struct Token {
+    r#type: String,
+}
+
+fn main() {
+    let err = Command::builder()
+        .arg("build".to_owned())
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        CommandBuilderError::UninitializedFields(vec![
+            "executable".to_owned(),
+            "env".to_owned(),
+            "timeout".to_owned(),
+        ]),
+    );
+    assert_eq!(
+        err.to_string(),
+        "missing required fields: `executable`, `env`, `timeout`",
+    );
+
+    let err: Box<dyn std::error::Error> = Command::builder()
+        .executable("cargo".to_owned())
+        .env(vec![])
+        .build()
+        .unwrap_err()
+        .into();
+    assert_eq!(err.to_string(), "missing required fields: `timeout`");
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .env(vec![])
+        .timeout(0)
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert!(command.args.is_empty());
+    assert!(command.current_dir.is_none());
+    assert_eq!(command.timeout, 0);
+
+    let err = Token::builder().build().unwrap_err();
+    assert_eq!(err.to_string(), "missing required fields: `type`");
+}
@@ -0,0 +1,58 @@
+// The builder carries over every generic parameter of the input struct:
+// lifetimes, type parameters with their bounds, const generics, defaults and
+// the where-clause.
+//
+//     pub struct RequestBuilder<'a, T: Display, const N: usize>
+//     where
+//         T: Clone,
+//     {
+//         ...
+//     }
+//
+// Bounds required by the generated code itself, such as Clone for the fields
+// copied out in `build`, are placed on the individual methods that need them
+// so that the builder type remains usable with any parameters the struct
+// accepts.
+
+use derive_builder::Builder;
+use std::fmt::Display;
+
+#[derive(Builder)]
+pub struct Request<'a, T: Display, const N: usize, U = u8>
+where
+    T: Clone,
+{
+    path: &'a str,
+    body: T,
+    #[builder(each = "header")]
+    headers: Vec<(&'a str, T)>,
+    checksum: [U; N],
+    retry: Option<U>,
+}
+
+fn main() {
+    let path = String::from("/index.html");
+    let request: Request<String, 2> = Request::builder()
+        .path(&path)
+        .body("hello".to_owned())
+        .header(("accept", "*/*".to_owned()))
+        .checksum([1, 2])
+        .build()
+        .unwrap();
+
+    assert_eq!(request.path, "/index.html");
+    assert_eq!(request.body.to_string(), "hello");
+    assert_eq!(request.headers.len(), 1);
+    assert_eq!(request.checksum, [1u8, 2]);
+    assert!(request.retry.is_none());
+
+    let err = Request::<i32, 0, u16>::builder()
+        .retry(3)
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "missing required fields: `path`, `body`, `checksum`"
+    );
+}
@@ -0,0 +1,49 @@
+// With #[builder(typestate)] the builder tracks in its type which required
+// fields have been set, so `build` is only callable once all of them are and
+// returns the struct directly instead of a Result.
+//
+// Each required field contributes one type parameter to the builder. Its
+// setter consumes the builder and returns it with that parameter changed from
+// Unset to Set, which costs nothing at runtime.
+//
+//     let builder: MessageBuilder<Unset, Unset> = Message::builder();
+//     let builder: MessageBuilder<Set<u32>, Unset> = builder.id(7);
+//     let builder: MessageBuilder<Set<u32>, Set<Vec<u8>>> = builder.payload(...);
+//     let message: Message = builder.build();
+//
+// Setters for optional and repeated fields leave the type unchanged and may
+// be called in any order.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Message<'a, T> {
+    id: u32,
+    kind: &'a str,
+    payload: T,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+    reply_to: Option<u32>,
+}
+
+fn main() {
+    let message = Message::builder()
+        .payload(vec![1u8, 2, 3])
+        .tag("urgent".to_owned())
+        .id(7)
+        .reply_to(6)
+        .kind("ping")
+        .tag("retry".to_owned())
+        .build();
+
+    assert_eq!(message.id, 7);
+    assert_eq!(message.kind, "ping");
+    assert_eq!(message.payload, [1, 2, 3]);
+    assert_eq!(message.tags, ["urgent", "retry"]);
+    assert_eq!(message.reply_to, Some(6));
+
+    let message = Message::builder().id(8).kind("pong").payload(()).build();
+    assert!(message.tags.is_empty());
+    assert!(message.reply_to.is_none());
+}
@@ -0,0 +1,16 @@
+// In typestate mode forgetting a required setter is a compile error that
+// names the missing field, rather than a runtime error from `build`.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Message {
+    id: u32,
+    payload: Vec<u8>,
+    reply_to: Option<u32>,
+}
+
+fn main() {
+    let _message = Message::builder().payload(vec![]).reply_to(1).build();
+}
@@ -0,0 +1,56 @@
+// The container attribute #[builder(pattern = "...")] chooses how setters and
+// `build` receive the builder.
+//
+//   - "mutable" (the default): setters take and return &mut Self, and `build`
+//     clones the fields out of &self.
+//
+//   - "owned": setters take self by value and return Self, and `build(self)`
+//     moves the fields into the target, so field types need not be Clone.
+//
+//   - "immutable": setters take &self and return a modified copy, leaving the
+//     builder they were called on unchanged.
+
+use derive_builder::Builder;
+use std::fmt::Debug;
+
+pub struct Connection {
+    fd: i32,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Session {
+    connection: Connection,
+    handler: Box<dyn Fn(&str) -> String>,
+    #[builder(each = "line")]
+    log: Vec<Box<dyn Debug>>,
+    name: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable")]
+pub struct Endpoint {
+    host: String,
+    port: u16,
+}
+
+fn main() {
+    let session = Session::builder()
+        .connection(Connection { fd: 3 })
+        .handler(Box::new(|line| line.to_uppercase()))
+        .line(Box::new("open"))
+        .build()
+        .unwrap();
+    assert_eq!(session.connection.fd, 3);
+    assert_eq!((session.handler)("ok"), "OK");
+    assert_eq!(session.log.len(), 1);
+    assert!(session.name.is_none());
+
+    let base = Endpoint::builder().host("localhost".to_owned());
+    let http = base.port(80).build().unwrap();
+    let https = base.port(443).build().unwrap();
+    assert_eq!(http.host, "localhost");
+    assert_eq!(http.port, 80);
+    assert_eq!(https.port, 443);
+    assert!(base.build().is_err());
+}
@@ -0,0 +1,71 @@
+// A field marked #[builder(default)] may be left unset, in which case `build`
+// fills it in from the field type's Default impl. An explicit fallback can be
+// given as an expression, either directly or inside a string literal:
+//
+//     #[builder(default = 8080)]
+//     #[builder(default = "default_host()")]
+//
+// Fields with a default do not count as missing in the builder error, and
+// setting them explicitly still overrides the default.
+
+use derive_builder::Builder;
+
+fn default_host() -> String {
+    "localhost".to_owned()
+}
+
+#[derive(Builder)]
+pub struct Server<T> {
+    name: String,
+    #[builder(default = "default_host()")]
+    host: String,
+    #[builder(default = 8000 + 80)]
+    port: u16,
+    #[builder(default)]
+    workers: usize,
+    #[builder(default)]
+    state: T,
+    #[builder(each = "alias", default = vec!["www".to_owned()])]
+    aliases: Vec<String>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Client {
+    server: String,
+    #[builder(default = 3)]
+    retries: u8,
+}
+
+fn main() {
+    let server = Server::<Vec<u8>>::builder()
+        .name("api".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.workers, 0);
+    assert!(server.state.is_empty());
+    assert_eq!(server.aliases, ["www"]);
+
+    let server = Server::builder()
+        .name("api".to_owned())
+        .host("example.com".to_owned())
+        .port(443)
+        .workers(4)
+        .state(true)
+        .alias("api".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(server.host, "example.com");
+    assert_eq!(server.port, 443);
+    assert_eq!(server.workers, 4);
+    assert!(server.state);
+    assert_eq!(server.aliases, ["www", "api"]);
+
+    let err = Server::<()>::builder().port(1).build().err().unwrap();
+    assert_eq!(err.to_string(), "missing required fields: `name`");
+
+    let client = Client::builder().server("example.com".to_owned()).build();
+    assert_eq!(client.retries, 3);
+}
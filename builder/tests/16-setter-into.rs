@@ -0,0 +1,79 @@
+// #[builder(setter(into))] makes a setter generic over `impl Into<T>`, so the
+// caller can pass a &str to a String field without converting it first. Put
+// on the struct, it applies to every field unless a field opts out with
+// #[builder(setter(into = false))].
+//
+// Setters of Option<T> fields take a bare T by default. With
+// #[builder(setter(strip_option = false))] they take the whole Option<T>
+// instead, which lets the caller reset a field to None. The container-level
+// form flips the default for all fields, and a field can opt back in with
+// #[builder(setter(strip_option))].
+
+use derive_builder::Builder;
+use std::path::PathBuf;
+
+#[derive(Builder)]
+#[builder(setter(into))]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<PathBuf>,
+    #[builder(setter(into = false))]
+    timeout: u64,
+    #[builder(setter(strip_option = false))]
+    user: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(setter(strip_option = false))]
+pub struct Mount {
+    source: Option<String>,
+    #[builder(setter(strip_option, into))]
+    target: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(typestate, setter(into))]
+pub struct Header {
+    name: String,
+    value: Option<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo")
+        .arg("build")
+        .arg(String::from("--release"))
+        .current_dir("/tmp")
+        .timeout(30)
+        .user(None::<String>)
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, ["build", "--release"]);
+    assert_eq!(command.current_dir, Some(PathBuf::from("/tmp")));
+    assert_eq!(command.timeout, 30);
+    assert!(command.user.is_none());
+
+    let command = Command::builder()
+        .executable("cargo")
+        .timeout(0)
+        .user(Some("root".to_owned()))
+        .build()
+        .unwrap();
+    assert_eq!(command.user.as_deref(), Some("root"));
+
+    let mount = Mount::builder()
+        .source(Some("tmpfs".to_owned()))
+        .source(None)
+        .target("/run")
+        .build()
+        .unwrap();
+    assert!(mount.source.is_none());
+    assert_eq!(mount.target.as_deref(), Some("/run"));
+
+    let header = Header::builder().name("accept").value("*/*").build();
+    assert_eq!(header.name, "accept");
+    assert_eq!(header.value.as_deref(), Some("*/*"));
+}
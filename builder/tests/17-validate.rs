@@ -0,0 +1,82 @@
+// #[builder(build_fn(validate = "path"))] names a function that `build` calls
+// with a reference to the builder once every required field has been set. It
+// returns Result<(), E> for any E that implements Display, and an error is
+// passed on to the caller as the Validation variant of the builder error.
+// Only the Display output of E is kept there; a custom error type (see
+// 32-custom-error.rs) receives E itself.
+//
+//     fn validate(builder: &RangeBuilder) -> Result<(), String>;
+//
+//     pub enum RangeBuilderError {
+//         UninitializedFields(Vec<String>),
+//         Validation(String),
+//     }
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct Range {
+    min: u32,
+    max: u32,
+    #[builder(default)]
+    step: Option<u32>,
+}
+
+impl RangeBuilder {
+    fn validate(&self) -> Result<(), String> {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min > max => Err(format!("min {} exceeds max {}", min, max)),
+            _ => Ok(()),
+        }
+    }
+}
+
+mod checks {
+    use std::fmt;
+
+    pub struct Zero;
+
+    impl fmt::Display for Zero {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("step must not be zero")
+        }
+    }
+
+    pub fn non_zero(builder: &super::StrideBuilder) -> Result<(), Zero> {
+        if builder.step == Some(0) {
+            Err(Zero)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned", build_fn(validate = checks::non_zero))]
+pub struct Stride {
+    pub step: u32,
+}
+
+fn main() {
+    let range = Range::builder().min(1).max(10).build().unwrap();
+    assert_eq!((range.min, range.max, range.step), (1, 10, None));
+
+    let err = Range::builder().min(10).max(1).build().unwrap_err();
+    assert_eq!(
+        err,
+        RangeBuilderError::Validation("min 10 exceeds max 1".to_owned()),
+    );
+    assert_eq!(err.to_string(), "min 10 exceeds max 1");
+
+    // Missing fields are reported before validation runs.
+    let err = Range::builder().min(10).build().unwrap_err();
+    assert_eq!(
+        err,
+        RangeBuilderError::UninitializedFields(vec!["max".to_owned()]),
+    );
+
+    assert_eq!(Stride::builder().step(2).build().ok().unwrap().step, 2);
+    let err = Stride::builder().step(0).build().err().unwrap();
+    assert_eq!(err.to_string(), "step must not be zero");
+}
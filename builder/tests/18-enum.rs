@@ -0,0 +1,75 @@
+// Deriving Builder on an enum generates one builder per variant, named after
+// the enum and the variant, and an associated function on the enum that
+// returns it:
+//
+//     impl Message {
+//         pub fn request_builder() -> MessageRequestBuilder { ... }
+//         pub fn response_builder() -> MessageResponseBuilder { ... }
+//         pub fn keep_alive_builder() -> MessageKeepAliveBuilder { ... }
+//     }
+//
+// Each variant builder supports the same field attributes as a struct
+// builder, and its `build` returns the enum.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Message<T> {
+    Request {
+        id: u32,
+        #[builder(each = "header")]
+        headers: Vec<String>,
+        body: Option<T>,
+    },
+    Response {
+        id: u32,
+        #[builder(default = 200)]
+        status: u16,
+    },
+    KeepAlive,
+}
+
+#[derive(Builder)]
+#[builder(typestate, setter(into))]
+pub enum Command {
+    Get { key: String },
+    Set { key: String, value: Vec<u8> },
+}
+
+fn main() {
+    let request = Message::request_builder()
+        .id(1)
+        .header("accept: */*".to_owned())
+        .body("ping")
+        .build()
+        .unwrap();
+    assert_eq!(
+        request,
+        Message::Request {
+            id: 1,
+            headers: vec!["accept: */*".to_owned()],
+            body: Some("ping"),
+        },
+    );
+
+    let response = Message::<()>::response_builder().id(1).build().unwrap();
+    assert_eq!(response, Message::Response { id: 1, status: 200 });
+
+    let err = Message::<()>::request_builder().build().unwrap_err();
+    assert_eq!(
+        err,
+        MessageRequestBuilderError::UninitializedFields(vec!["id".to_owned()]),
+    );
+
+    let keep_alive = Message::<()>::keep_alive_builder().build().unwrap();
+    assert_eq!(keep_alive, Message::KeepAlive);
+
+    match Command::set_builder().value(vec![1]).key("k").build() {
+        Command::Set { key, value } => assert_eq!((key.as_str(), value), ("k", vec![1])),
+        Command::Get { .. } => unreachable!(),
+    }
+    assert!(matches!(
+        Command::get_builder().key("k").build(),
+        Command::Get { .. }
+    ));
+}
@@ -0,0 +1,51 @@
+// Tuple structs, newtypes and tuple variants get a builder as well. Their
+// setters are named after the field positions, `_0`, `_1` and so on, unless a
+// field picks its own name with #[builder(name = "...")]. The name also
+// appears in the missing field error.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct UserId(u64);
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Span(
+    #[builder(name = "start")] usize,
+    #[builder(name = "end")] usize,
+    Option<String>,
+);
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Shape {
+    Circle(#[builder(name = "radius")] f64),
+    Rect(f64, f64),
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Point(i32, i32);
+
+fn main() {
+    assert_eq!(UserId::builder()._0(7).build().unwrap(), UserId(7));
+
+    let span = Span::builder().start(1).end(4).build().unwrap();
+    assert_eq!(span, Span(1, 4, None));
+    let span = Span::builder()
+        .end(4)
+        .start(1)
+        ._2("main.rs".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(span, Span(1, 4, Some("main.rs".to_owned())));
+
+    let err = Span::builder().start(1).build().unwrap_err();
+    assert_eq!(err.to_string(), "missing required fields: `end`");
+
+    let circle = Shape::circle_builder().radius(1.5).build().unwrap();
+    assert_eq!(circle, Shape::Circle(1.5));
+    let rect = Shape::rect_builder()._0(2.0)._1(3.0).build().unwrap();
+    assert_eq!(rect, Shape::Rect(2.0, 3.0));
+
+    let point = Point::builder()._1(2)._0(1).build();
+    assert_eq!((point.0, point.1), (1, 2));
+}
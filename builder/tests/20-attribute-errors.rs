@@ -0,0 +1,41 @@
+// Every mistake in the builder attributes should be reported at once, each
+// pointing at the offending tokens, rather than only the first one or a panic
+// inside the macro.
+//
+// This covers an option that does not exist, an option given twice, a value
+// that should have been a string literal, `each` on a field that is not a
+// collection, `optional` on a type that cannot be an `Option`, `try_setter`
+// without a setter for the whole field, setters named like the builder's
+// introspection methods, and a mistake in the container attribute alongside
+// mistakes in the fields. An enum cannot take a validation function, since
+// each of its variants has a builder of a different type.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(patern = "owned")]
+pub struct Command {
+    #[builder(each = "arg", each = "argument")]
+    args: Vec<String>,
+    #[builder(each = env)]
+    env: Vec<String>,
+    #[builder(each = "dir")]
+    current_dir: String,
+    #[builder(setter(strip_option))]
+    executable: String,
+    #[builder(optional)]
+    timeout: u64,
+    #[builder(each = "flags", try_setter)]
+    flags: Vec<String>,
+    is_complete: bool,
+    is_set_timeout: bool,
+}
+
+#[derive(Builder)]
+#[builder(build_fn(validate = "check_shape"))]
+pub enum Shape {
+    Circle { radius: f64 },
+    Square { side: f64 },
+}
+
+fn main() {}
@@ -0,0 +1,53 @@
+// The `each` setter is not limited to `Vec`. Items are added through `Extend`,
+// so the standard lists, sets and maps all work, with a map's setter taking a
+// key and a value:
+//
+//     fn env(&mut self, key: String, value: String) -> &mut Self
+//
+// For any other collection implementing `Extend` and `Default`, the item type
+// cannot be read off the field type and is given explicitly:
+//
+//     #[builder(each(name = "flag", item = "char"))]
+
+use derive_builder::Builder;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: VecDeque<String>,
+    #[builder(each = "env")]
+    env: std::collections::HashMap<String, String>,
+    #[builder(each = "group")]
+    groups: BTreeSet<u32>,
+    #[builder(each = "limit", setter(into))]
+    limits: BTreeMap<String, u64>,
+    #[builder(each(name = "flag", item = "char"))]
+    flags: String,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .arg("--release".to_owned())
+        .env("RUSTFLAGS".to_owned(), "-Dwarnings".to_owned())
+        .group(20)
+        .group(10)
+        .group(20)
+        .limit("nofile", 1024u32)
+        .flag('v')
+        .flag('q')
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, ["build", "--release"]);
+    let mut env = HashMap::new();
+    env.insert("RUSTFLAGS".to_owned(), "-Dwarnings".to_owned());
+    assert_eq!(command.env, env);
+    assert_eq!(command.groups.into_iter().collect::<Vec<_>>(), [10, 20]);
+    assert_eq!(command.limits["nofile"], 1024);
+    assert_eq!(command.flags, "vq");
+}
@@ -0,0 +1,53 @@
+// A field with an `each` setter keeps its setter for the whole collection as
+// long as the two names differ, and also gets an `extend_` setter that
+// appends everything from an iterator:
+//
+//     fn args(&mut self, args: Vec<String>) -> &mut Self      // replaces
+//     fn arg(&mut self, arg: String) -> &mut Self             // appends one
+//     fn extend_args(&mut self, args: impl IntoIterator<Item = String>) -> &mut Self
+//
+// This lets a builder be filled from a parsed config file as easily as from
+// fluent code.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env")]
+    env: HashMap<String, String>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Job {
+    #[builder(each = "step")]
+    steps: Vec<String>,
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    builder
+        .executable("cargo".to_owned())
+        .arg("check".to_owned())
+        .args(vec!["build".to_owned()])
+        .arg("--release".to_owned())
+        .extend_args(["--locked".to_owned()])
+        .extend_env(vec![("A".to_owned(), "1".to_owned())])
+        .env("B".to_owned(), "2".to_owned());
+    let command = builder.build().unwrap();
+
+    assert_eq!(command.args, ["build", "--release", "--locked"]);
+    assert_eq!(command.env.len(), 2);
+    assert_eq!(command.env["A"], "1");
+
+    let job = Job::builder()
+        .steps(vec!["fetch".to_owned()])
+        .extend_steps(["build".to_owned(), "test".to_owned()])
+        .step("deploy".to_owned())
+        .build();
+    assert_eq!(job.steps, ["fetch", "build", "test", "deploy"]);
+}
@@ -0,0 +1,157 @@
+// A field whose type derives Builder itself can be filled through its own
+// builder with `#[builder(sub_builder)]`. The outer builder holds an
+// `InnerBuilder` for the field and hands it out for configuration:
+//
+//     fn listen_mut(&mut self) -> &mut ListenBuilder
+//
+// `build` builds the inner value as well. A required field missing from the
+// inner builder is reported by the outer `build` with its path from the outer
+// type, alongside the outer type's own missing fields. The inner builder is
+// built with its own `build`, whatever it is named, and a validation error it
+// returns, of whichever type, is reported through its Display with the
+// field's name in front. An inner builder using the owned pattern can only be
+// nested in a builder using it too, since the others build from a reference,
+// and is replaced as a whole through `_mut`.
+
+use derive_builder::{Builder, UninitializedFieldError};
+use std::fmt;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Listen {
+    host: String,
+    port: u16,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Server {
+    name: String,
+    #[builder(sub_builder)]
+    listen: Listen,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Config {
+    #[builder(sub_builder)]
+    server: Server,
+    workers: usize,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(build_fn(name = "finish"))]
+pub struct Tls {
+    cert: String,
+}
+
+#[derive(Debug)]
+pub enum LimitsError {
+    Missing(Vec<String>),
+    Invalid(String),
+}
+
+impl From<UninitializedFieldError> for LimitsError {
+    fn from(err: UninitializedFieldError) -> Self {
+        LimitsError::Missing(err.fields().to_vec())
+    }
+}
+
+impl fmt::Display for LimitsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LimitsError::Missing(fields) => write!(f, "missing {}", fields.join(", ")),
+            LimitsError::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(build_fn(error = "LimitsError", validate = "check_limits"))]
+pub struct Limits {
+    max_connections: u32,
+}
+
+fn check_limits(builder: &LimitsBuilder) -> Result<(), LimitsError> {
+    match builder.max_connections {
+        Some(0) => Err(LimitsError::Invalid(
+            "max_connections must not be zero".to_owned(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Proxy {
+    #[builder(sub_builder)]
+    tls: Tls,
+    #[builder(sub_builder)]
+    limits: Limits,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(pattern = "owned")]
+pub struct Upstream {
+    address: String,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(pattern = "owned")]
+pub struct Route {
+    path: String,
+    #[builder(sub_builder)]
+    upstream: Upstream,
+}
+
+fn main() {
+    let mut builder = Server::builder();
+    builder.name("web".to_owned());
+    builder.listen_mut().host("localhost".to_owned()).port(8080);
+    let server = builder.build().unwrap();
+    assert_eq!(
+        server.listen,
+        Listen {
+            host: "localhost".to_owned(),
+            port: 8080,
+        }
+    );
+
+    let mut builder = Config::builder();
+    builder
+        .server_mut()
+        .listen_mut()
+        .host("localhost".to_owned());
+    let err = builder.build().unwrap_err();
+    assert_eq!(
+        err,
+        ConfigBuilderError::UninitializedFields(vec![
+            "server.name".to_owned(),
+            "server.listen.port".to_owned(),
+            "workers".to_owned(),
+        ])
+    );
+    assert_eq!(
+        err.to_string(),
+        "missing required fields: `server.name`, `server.listen.port`, `workers`",
+    );
+
+    let mut builder = Proxy::builder();
+    builder.tls_mut().cert("cert.pem".to_owned());
+    builder.limits_mut().max_connections(0);
+    assert_eq!(
+        builder.build().unwrap_err().to_string(),
+        "limits: max_connections must not be zero",
+    );
+    builder.limits_mut().max_connections(64);
+    let proxy = builder.build().unwrap();
+    assert_eq!(proxy.tls.cert, "cert.pem");
+    assert_eq!(proxy.limits.max_connections, 64);
+
+    let err = Proxy::builder().build().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "missing required fields: `tls.cert`, `limits.max_connections`",
+    );
+
+    let mut builder = Route::builder().path("/api".to_owned());
+    *builder.upstream_mut() = Upstream::builder().address("10.0.0.1:80".to_owned());
+    let route = builder.build().unwrap();
+    assert_eq!(route.upstream.address, "10.0.0.1:80");
+}
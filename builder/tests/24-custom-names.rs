@@ -0,0 +1,77 @@
+// The names and visibility of everything generated can be adjusted:
+//
+//     #[builder(
+//         name = "ConfigMaker",              // instead of `ConfigBuilder`
+//         vis = "pub(crate)",                // instead of the struct's own
+//         derive(Debug, Clone, PartialEq),   // extra derives on the builder
+//         build_fn(name = "finish"),         // instead of `build`
+//         builder_fn(name = "new_builder"),  // instead of `builder`
+//     )]
+//
+// and per field with `setter(name = "...", vis = "...")`. Without `vis`, the
+// builder is exactly as visible as the derived type, and its setters are as
+// visible as the builder.
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Builder, Debug)]
+    #[builder(
+        name = "ConfigMaker",
+        vis = "pub(crate)",
+        derive(Debug, Clone, PartialEq),
+        build_fn(name = "finish"),
+        builder_fn(name = "new_builder")
+    )]
+    pub struct Config {
+        #[builder(setter(name = "with_port"))]
+        pub port: u16,
+        #[builder(setter(vis = "pub(self)"), default = 4)]
+        pub workers: usize,
+    }
+
+    impl ConfigMaker {
+        pub fn single_threaded(&mut self) -> &mut Self {
+            self.workers(1)
+        }
+    }
+
+    // A private struct gets a private builder.
+    #[derive(Builder)]
+    struct Internal {
+        id: u32,
+    }
+
+    pub fn internal_id() -> u32 {
+        Internal::builder().id(7).build().unwrap().id
+    }
+}
+
+use config::{Config, ConfigMaker, ConfigMakerError};
+
+fn main() {
+    let mut builder: ConfigMaker = Config::new_builder();
+    builder.with_port(8080);
+    let copy = builder.clone();
+    assert_eq!(copy, builder);
+    assert_eq!(
+        format!("{:?}", builder),
+        "ConfigMaker { port: Some(8080), workers: None }",
+    );
+
+    let config = builder.finish().unwrap();
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.workers, 4);
+
+    let config = Config::new_builder()
+        .with_port(80)
+        .single_threaded()
+        .finish()
+        .unwrap();
+    assert_eq!(config.workers, 1);
+
+    let err: ConfigMakerError = Config::new_builder().finish().unwrap_err();
+    assert_eq!(err.to_string(), "missing required fields: `port`");
+
+    assert_eq!(config::internal_id(), 7);
+}
@@ -0,0 +1,61 @@
+// Some fields should never be set through the builder. With
+// `#[builder(setter(skip))]` a field is left out of the builder entirely and
+// `build` fills it from its default, either `Default::default()` or the
+// expression given with `default = ...`.
+//
+// A field marked `#[builder(private)]` keeps its setter, but the setter is
+// private to the module defining the struct, so that a wrapper there can set
+// it on the caller's behalf.
+
+mod connection {
+    use derive_builder::Builder;
+    use std::collections::HashMap;
+    use std::marker::PhantomData;
+
+    pub struct Tcp;
+
+    #[derive(Builder)]
+    pub struct Connection<P> {
+        pub host: String,
+        #[builder(private)]
+        pub port: u16,
+        #[builder(setter(skip))]
+        pub cache: HashMap<String, String>,
+        #[builder(setter(skip), default = 3)]
+        pub retries: u32,
+        #[builder(setter(skip))]
+        pub protocol: PhantomData<P>,
+    }
+
+    impl ConnectionBuilder<Tcp> {
+        pub fn https(&mut self) -> &mut Self {
+            self.port(443)
+        }
+    }
+
+    #[derive(Builder)]
+    #[builder(typestate)]
+    pub struct Session {
+        pub user: String,
+        #[builder(setter(skip), default = 60)]
+        pub timeout: u64,
+    }
+}
+
+use connection::{Connection, Session, Tcp};
+
+fn main() {
+    let connection = Connection::<Tcp>::builder()
+        .host("example.com".to_owned())
+        .https()
+        .build()
+        .unwrap();
+    assert_eq!(connection.host, "example.com");
+    assert_eq!(connection.port, 443);
+    assert!(connection.cache.is_empty());
+    assert_eq!(connection.retries, 3);
+
+    let session = Session::builder().user("root".to_owned()).build();
+    assert_eq!(session.user, "root");
+    assert_eq!(session.timeout, 60);
+}
@@ -0,0 +1,55 @@
+// The builder never needs a field's type to implement Default unless the
+// field asks for it with `#[builder(default)]`. Required fields start out
+// unset and `Option` fields start out as `None`, whatever their type, so a
+// struct holding something like a `TcpListener` can derive Builder too. It
+// needs `pattern = "owned"` though: the other patterns build from a reference
+// to the builder and clone every field out of it, so their fields must be
+// `Clone` (see 38-mutable-needs-clone.rs).
+
+use derive_builder::Builder;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Port(u16);
+
+// Neither Default nor Clone, like a socket.
+pub struct Listener {
+    port: Port,
+}
+
+#[derive(Builder)]
+pub struct Endpoint {
+    port: Port,
+    fallback: Option<Port>,
+    #[builder(each = "alias")]
+    aliases: Vec<Port>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Server {
+    listener: Listener,
+    backlog: Option<Listener>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Client {
+    remote: Port,
+    local: Option<Port>,
+}
+
+fn main() {
+    let endpoint = Endpoint::builder().port(Port(80)).build().unwrap();
+    assert_eq!(endpoint.port, Port(80));
+    assert_eq!(endpoint.fallback, None);
+    assert!(endpoint.aliases.is_empty());
+
+    let listener = Listener { port: Port(8080) };
+    let server = Server::builder().listener(listener).build().unwrap();
+    assert_eq!(server.listener.port, Port(8080));
+    assert!(server.backlog.is_none());
+
+    let client = Client::builder().remote(Port(443)).build();
+    assert_eq!(client.remote, Port(443));
+    assert_eq!(client.local, None);
+}
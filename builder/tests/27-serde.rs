@@ -0,0 +1,101 @@
+// With the `serde` feature of derive_builder enabled, `#[builder(serde)]`
+// makes the builder deserializable with every field optional, so a partial
+// configuration can be loaded straight into it. Builders loaded from several
+// sources are combined with `merge`, where fields set in the later builder
+// win and collections are appended to. A field that is missing from a layer
+// leaves the earlier layers alone, even when it has an explicit default:
+//
+//     let mut config: ConfigBuilder = serde_json::from_str(defaults)?;
+//     config.merge(serde_json::from_str(overrides)?);
+//     let config = config.build()?;
+//
+// The crate using this needs its own dependency on serde.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(serde)]
+pub struct Listen {
+    host: String,
+    port: u16,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(serde)]
+pub struct Config {
+    name: String,
+    #[builder(default = 4)]
+    workers: usize,
+    log_file: Option<String>,
+    #[builder(each = "plugin")]
+    plugins: Vec<String>,
+    #[builder(sub_builder)]
+    listen: Listen,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(serde)]
+pub struct Logging {
+    #[builder(default = Some("info".to_owned()))]
+    log_level: Option<String>,
+    #[builder(each = "alias", default = vec!["www".to_owned()])]
+    aliases: Vec<String>,
+}
+
+fn main() {
+    let defaults = r#"{
+        "workers": 8,
+        "plugins": ["metrics"],
+        "listen": { "host": "0.0.0.0", "port": 80 }
+    }"#;
+    let overrides = r#"{
+        "name": "web",
+        "plugins": ["auth"],
+        "listen": { "port": 8080 }
+    }"#;
+
+    let mut builder: ConfigBuilder = serde_json::from_str(defaults).unwrap();
+    assert!(builder.build().is_err());
+    builder.merge(serde_json::from_str(overrides).unwrap());
+    builder.plugin("tracing".to_owned());
+    let config = builder.build().unwrap();
+
+    assert_eq!(
+        config,
+        Config {
+            name: "web".to_owned(),
+            workers: 8,
+            log_file: None,
+            plugins: vec![
+                "metrics".to_owned(),
+                "auth".to_owned(),
+                "tracing".to_owned(),
+            ],
+            listen: Listen {
+                host: "0.0.0.0".to_owned(),
+                port: 8080,
+            },
+        }
+    );
+
+    let mut builder: LoggingBuilder =
+        serde_json::from_str(r#"{ "log_level": "debug", "aliases": ["api"] }"#).unwrap();
+    builder.merge(serde_json::from_str("{}").unwrap());
+    let logging = builder.build().unwrap();
+    assert_eq!(logging.log_level.as_deref(), Some("debug"));
+    assert_eq!(logging.aliases, ["api"]);
+
+    let logging = serde_json::from_str::<LoggingBuilder>("{}")
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(logging.log_level.as_deref(), Some("info"));
+    assert_eq!(logging.aliases, ["www"]);
+
+    let empty: ConfigBuilder = serde_json::from_str("{}").unwrap();
+    let err = empty.build().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "missing required fields: `name`, `listen.host`, `listen.port`",
+    );
+}
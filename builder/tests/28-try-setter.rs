@@ -0,0 +1,63 @@
+// With `#[builder(try_setter)]`, on a field or on the container for every
+// field, each setter gets a fallible sibling that accepts anything convertible
+// with `TryInto`:
+//
+//     fn try_workers<V: TryInto<NonZeroU32>>(&mut self, workers: V)
+//         -> Result<&mut Self, V::Error>
+//
+// which is handy for values that arrive in a wider type or as text.
+
+use derive_builder::Builder;
+use std::net::IpAddr;
+use std::num::NonZeroU32;
+
+#[derive(Clone)]
+pub struct Host(IpAddr);
+
+impl TryFrom<&str> for Host {
+    type Error = std::net::AddrParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse().map(Host)
+    }
+}
+
+#[derive(Builder)]
+pub struct Server {
+    #[builder(try_setter)]
+    workers: NonZeroU32,
+    #[builder(try_setter)]
+    bind: Option<Host>,
+    name: String,
+}
+
+#[derive(Builder)]
+#[builder(typestate, try_setter)]
+pub struct Limits {
+    open_files: u16,
+    threads: Option<u8>,
+}
+
+fn main() {
+    let mut builder = Server::builder();
+    builder.try_workers(4u32).unwrap();
+    assert!(builder.try_workers(0u32).is_err());
+    builder
+        .try_bind("127.0.0.1")
+        .unwrap()
+        .name("web".to_owned());
+    assert!(builder.try_bind("localhost").is_err());
+    let server = builder.build().unwrap();
+    assert_eq!(server.workers.get(), 4);
+    assert_eq!(server.bind.unwrap().0, IpAddr::from([127, 0, 0, 1]));
+
+    let limits = Limits::builder()
+        .try_open_files(1024u64)
+        .unwrap()
+        .try_threads(16i64)
+        .unwrap()
+        .build();
+    assert_eq!(limits.open_files, 1024);
+    assert_eq!(limits.threads, Some(16));
+    assert!(Limits::builder().try_open_files(-1i32).is_err());
+}
@@ -0,0 +1,90 @@
+// With `#[builder(to_builder)]` an existing value can be turned back into a
+// builder, changed, and built again with every check `build` performs:
+//
+//     impl From<Config> for ConfigBuilder { ... }
+//
+//     impl Config {
+//         pub fn to_builder(&self) -> ConfigBuilder where Self: Clone { ... }
+//     }
+//
+// Every field of the builder starts out set from the value, `each` setters
+// append to the existing collection, and fields skipped by the builder are
+// dropped and filled from their defaults again. A sub-builder is made from
+// the inner value the same way, so its type needs `to_builder` as well.
+
+use derive_builder::Builder;
+
+fn check(builder: &ConfigBuilder) -> Result<(), String> {
+    match builder.port {
+        Some(0) => Err("port must not be 0".to_owned()),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(to_builder)]
+pub struct Limits {
+    max_connections: u32,
+    timeout: Option<u32>,
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(to_builder, build_fn(validate = check))]
+pub struct Config {
+    host: String,
+    port: u16,
+    #[builder(sub_builder)]
+    limits: Limits,
+    user: Option<String>,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+    #[builder(setter(skip))]
+    connections: usize,
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(typestate, to_builder)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn main() {
+    let mut builder = Config::builder();
+    builder
+        .host("localhost".to_owned())
+        .port(80)
+        .tag("web".to_owned());
+    builder.limits_mut().max_connections(16);
+    let mut config = builder.build().unwrap();
+    config.connections = 5;
+
+    let mut builder = config.to_builder();
+    builder
+        .port(8080)
+        .user("admin".to_owned())
+        .tag("prod".to_owned());
+    builder.limits_mut().timeout(30);
+    let changed = builder.build().unwrap();
+    assert_eq!(
+        changed,
+        Config {
+            host: "localhost".to_owned(),
+            port: 8080,
+            user: Some("admin".to_owned()),
+            limits: Limits {
+                max_connections: 16,
+                timeout: Some(30),
+            },
+            tags: vec!["web".to_owned(), "prod".to_owned()],
+            connections: 0,
+        }
+    );
+
+    let err = ConfigBuilder::from(config).port(0).build().unwrap_err();
+    assert_eq!(err.to_string(), "port must not be 0");
+
+    let point = Point::builder().x(1).y(2).build();
+    let moved = point.to_builder().y(5).build();
+    assert_eq!(moved, Point { x: 1, y: 5 });
+}
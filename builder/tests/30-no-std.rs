@@ -0,0 +1,80 @@
+// With `#[builder(no_std)]` the generated code refers only to `core` and
+// `alloc`, so the derive works in crates without the standard library. The
+// crate has to declare `extern crate alloc;` itself. The error type then
+// implements `core::error::Error`.
+//
+// Here the module below shadows `std`, so the derive only compiles if nothing
+// it generates goes through the standard library.
+
+extern crate alloc;
+
+mod firmware {
+    #[allow(dead_code)]
+    mod std {}
+
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use derive_builder::Builder;
+
+    fn check(builder: &SensorBuilder) -> Result<(), &'static str> {
+        match builder.rate {
+            Some(0) => Err("rate must not be 0"),
+            _ => Ok(()),
+        }
+    }
+
+    #[derive(Builder, Debug, PartialEq)]
+    #[builder(no_std)]
+    pub struct Calibration {
+        pub offset: i16,
+    }
+
+    #[derive(Builder, Debug, PartialEq)]
+    #[builder(no_std, build_fn(validate = check))]
+    pub struct Sensor {
+        pub id: u8,
+        pub rate: u32,
+        pub label: Option<String>,
+        #[builder(each = "channel")]
+        pub channels: Vec<u8>,
+        #[builder(sub_builder)]
+        pub calibration: Calibration,
+    }
+
+    pub fn error_is_core_error(err: SensorBuilderError) -> impl core::error::Error {
+        err
+    }
+}
+
+use firmware::{Calibration, Sensor};
+
+fn main() {
+    let mut builder = Sensor::builder();
+    builder.id(1).rate(100).channel(3);
+    builder.calibration_mut().offset(-2);
+    let sensor = builder.build().unwrap();
+    assert_eq!(
+        sensor,
+        Sensor {
+            id: 1,
+            rate: 100,
+            label: None,
+            channels: vec![3],
+            calibration: Calibration { offset: -2 },
+        }
+    );
+
+    let err = Sensor::builder().rate(0).build().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "missing required fields: `id`, `calibration.offset`",
+    );
+    let _ = firmware::error_is_core_error(err);
+
+    let mut builder = Sensor::builder();
+    builder.id(1).rate(0).calibration_mut().offset(0);
+    assert_eq!(
+        builder.build().unwrap_err().to_string(),
+        "rate must not be 0"
+    );
+}
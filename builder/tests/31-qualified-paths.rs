@@ -0,0 +1,59 @@
+// Optional fields and `each` collections are recognized by the last segment of
+// their type's path, so qualified names such as `std::option::Option<T>` or
+// `alloc::vec::Vec<T>` behave like the bare ones.
+//
+// A macro only sees tokens, so an alias of `Option` cannot be recognized and
+// is marked `#[builder(optional)]` instead. Its setter takes the whole field.
+// Conversely `#[builder(required)]` makes an `Option` field one the caller
+// has to set, with a setter taking the whole `Option`.
+
+extern crate alloc;
+
+use derive_builder::Builder;
+
+type MaybePort = Option<u16>;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Server {
+    host: ::std::string::String,
+    user: std::option::Option<String>,
+    group: core::option::Option<String>,
+    #[builder(each = "alias")]
+    aliases: alloc::vec::Vec<String>,
+    #[builder(each = "plugin")]
+    plugins: ::std::vec::Vec<String>,
+    #[builder(optional)]
+    port: MaybePort,
+    #[builder(required)]
+    proxy: Option<String>,
+}
+
+fn main() {
+    let server = Server::builder()
+        .host("localhost".to_owned())
+        .user("admin".to_owned())
+        .alias("web".to_owned())
+        .plugin("auth".to_owned())
+        .port(Some(8080))
+        .proxy(None)
+        .build()
+        .unwrap();
+    assert_eq!(
+        server,
+        Server {
+            host: "localhost".to_owned(),
+            user: Some("admin".to_owned()),
+            group: None,
+            aliases: vec!["web".to_owned()],
+            plugins: vec!["auth".to_owned()],
+            port: Some(8080),
+            proxy: None,
+        }
+    );
+
+    let err = Server::builder()
+        .host("localhost".to_owned())
+        .build()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "missing required fields: `proxy`");
+}
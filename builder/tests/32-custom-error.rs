@@ -0,0 +1,86 @@
+// With `#[builder(build_fn(error = "ConfigError"))]`, `build` returns the
+// application's own error type instead of the generated `ConfigBuilderError`.
+// The type only has to be convertible from the missing fields, and from the
+// error of the validation function, which is passed on as it is:
+//
+//     impl From<derive_builder::UninitializedFieldError> for ConfigError
+//     impl From<PortError> for ConfigError
+//
+// so that a builder fits into an existing error hierarchy and `?` works
+// without a `map_err`. With sub-builders, it also has to be convertible from
+// `derive_builder::ValidationError`, which carries the message of a failed
+// sub-builder.
+
+use derive_builder::{Builder, UninitializedFieldError, ValidationError};
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    Missing(Vec<String>),
+    Invalid(String),
+    Port(PortError),
+}
+
+// Neither Display nor Error is needed for the validation error.
+#[derive(Debug, PartialEq)]
+pub enum PortError {
+    Zero,
+}
+
+impl From<PortError> for ConfigError {
+    fn from(err: PortError) -> Self {
+        ConfigError::Port(err)
+    }
+}
+
+impl From<UninitializedFieldError> for ConfigError {
+    fn from(err: UninitializedFieldError) -> Self {
+        ConfigError::Missing(err.fields().to_vec())
+    }
+}
+
+impl From<ValidationError> for ConfigError {
+    fn from(err: ValidationError) -> Self {
+        ConfigError::Invalid(err.message().to_owned())
+    }
+}
+
+#[derive(Builder, Debug)]
+#[builder(build_fn(error = "ConfigError", validate = "check_port"))]
+pub struct Config {
+    host: String,
+    port: u16,
+}
+
+fn check_port(builder: &ConfigBuilder) -> Result<(), PortError> {
+    match builder.port {
+        Some(0) => Err(PortError::Zero),
+        _ => Ok(()),
+    }
+}
+
+fn load() -> Result<Config, ConfigError> {
+    let mut builder = Config::builder();
+    builder.host("localhost".to_owned()).port(8080);
+    let config = builder.build()?;
+    Ok(config)
+}
+
+fn main() {
+    let config = load().unwrap();
+    assert_eq!(config.host, "localhost");
+    assert_eq!(config.port, 8080);
+
+    let err = Config::builder().build().unwrap_err();
+    assert_eq!(
+        err,
+        ConfigError::Missing(vec!["host".to_owned(), "port".to_owned()]),
+    );
+
+    let mut builder = Config::builder();
+    builder.host("localhost".to_owned()).port(0);
+    let err = builder.build().unwrap_err();
+    assert_eq!(err, ConfigError::Port(PortError::Zero));
+
+    let err = UninitializedFieldError::new(vec!["host".to_owned(), "port".to_owned()]);
+    assert_eq!(err.to_string(), "missing required fields: `host`, `port`");
+}
@@ -0,0 +1,138 @@
+// Every builder can report what it still needs, for example to prompt only
+// for the missing values:
+//
+//     const FIELDS: &'static [FieldInfo];
+//     fn is_set_port(&self) -> bool;
+//     fn missing_fields(&self) -> Vec<&'static str>;
+//     fn is_complete(&self) -> bool;
+//
+// A field counts as set once it holds a value, and a sub-builder once it could
+// be built, and a collection filled through `each` once any of its setters
+// was called, even with no items. Only required fields and sub-builders can be
+// missing. A typestate builder answers the same questions from its type
+// parameters.
+
+use derive_builder::{Builder, FieldInfo, FieldKind};
+
+#[derive(Builder)]
+pub struct Listen {
+    host: String,
+    port: u16,
+}
+
+#[derive(Builder)]
+pub struct Server {
+    name: String,
+    #[builder(sub_builder)]
+    listen: Listen,
+    timeout: Option<u32>,
+    #[builder(default = "4")]
+    workers: usize,
+    #[builder(each = "alias")]
+    aliases: Vec<String>,
+}
+
+#[derive(Default, Clone)]
+pub struct Bag(Vec<u8>);
+
+impl Extend<u8> for Bag {
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+#[derive(Builder)]
+pub struct Packet {
+    #[builder(each(name = "byte", item = "u8"))]
+    payload: Bag,
+}
+
+#[derive(Builder)]
+pub struct Token {
+    r#type: String,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Message {
+    id: u64,
+    body: String,
+    reply_to: Option<u64>,
+}
+
+fn main() {
+    assert_eq!(
+        ServerBuilder::FIELDS,
+        &[
+            FieldInfo {
+                name: "name",
+                ty: "String",
+                kind: FieldKind::Required,
+            },
+            FieldInfo {
+                name: "listen",
+                ty: "Listen",
+                kind: FieldKind::SubBuilder,
+            },
+            FieldInfo {
+                name: "timeout",
+                ty: "Option<u32>",
+                kind: FieldKind::Optional,
+            },
+            FieldInfo {
+                name: "workers",
+                ty: "usize",
+                kind: FieldKind::Optional,
+            },
+            FieldInfo {
+                name: "aliases",
+                ty: "Vec<String>",
+                kind: FieldKind::Each,
+            },
+        ],
+    );
+
+    let mut builder = Server::builder();
+    assert!(!builder.is_complete());
+    assert_eq!(builder.missing_fields(), ["name", "listen"]);
+    assert!(!builder.is_set_workers());
+    assert!(!builder.is_set_aliases());
+
+    builder.name("web".to_owned()).alias("www".to_owned());
+    builder.listen_mut().host("localhost".to_owned());
+    assert!(builder.is_set_name());
+    assert!(builder.is_set_aliases());
+    assert!(!builder.is_set_listen());
+    assert_eq!(builder.missing_fields(), ["listen"]);
+    assert_eq!(builder.listen_mut().missing_fields(), ["port"]);
+
+    builder.listen_mut().port(8080);
+    builder.workers(8);
+    assert!(builder.is_set_workers());
+    assert!(!builder.is_set_timeout());
+    assert!(builder.is_complete());
+    assert!(builder.missing_fields().is_empty());
+
+    let mut builder = Packet::builder();
+    assert!(!builder.is_set_payload());
+    builder.extend_payload([]);
+    assert!(builder.is_set_payload());
+    builder.byte(1);
+    assert!(builder.is_complete());
+    assert_eq!(PacketBuilder::FIELDS[0].kind, FieldKind::Each);
+
+    let builder = Token::builder();
+    assert_eq!(TokenBuilder::FIELDS[0].name, "type");
+    assert_eq!(builder.missing_fields(), ["type"]);
+    assert!(!builder.is_set_type());
+
+    let builder = Message::builder();
+    assert_eq!(<MessageBuilder>::FIELDS.len(), 3);
+    assert_eq!(builder.missing_fields(), ["id", "body"]);
+    let builder = builder.id(1);
+    assert!(builder.is_set_id());
+    assert!(!builder.is_set_reply_to());
+    assert_eq!(builder.missing_fields(), ["body"]);
+    let builder = builder.body("hello".to_owned());
+    assert!(builder.is_complete());
+}
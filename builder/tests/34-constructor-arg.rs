@@ -0,0 +1,50 @@
+// A field marked `#[builder(constructor_arg)]` is passed to the builder
+// function instead of being set afterwards. The arguments come in declaration
+// order and follow the setter options of their field, such as `into`:
+//
+//     fn builder(id: u64, name: impl Into<String>) -> UserBuilder
+//
+// Such a field is set from the start, so `build` can never report it missing.
+// Its setter remains to change the value later.
+
+use derive_builder::{Builder, FieldKind};
+
+#[derive(Builder, Debug)]
+pub struct User {
+    #[builder(constructor_arg)]
+    id: u64,
+    email: String,
+    #[builder(constructor_arg, setter(into))]
+    name: String,
+    nickname: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Job {
+    #[builder(constructor_arg)]
+    id: u64,
+    command: String,
+}
+
+fn main() {
+    let mut builder = User::builder(7, "Ada");
+    assert!(builder.is_set_id());
+    assert_eq!(builder.missing_fields(), ["email"]);
+    assert_eq!(UserBuilder::FIELDS[0].kind, FieldKind::ConstructorArg);
+
+    builder.email("ada@example.com".to_owned());
+    let user = builder.build().unwrap();
+    assert_eq!(user.id, 7);
+    assert_eq!(user.name, "Ada");
+    assert_eq!(user.email, "ada@example.com");
+    assert_eq!(user.nickname, None);
+
+    let mut builder = User::builder(7, "Ada");
+    builder.id(8).email("ada@example.com".to_owned());
+    assert_eq!(builder.build().unwrap().id, 8);
+
+    let job = Job::builder(3).command("make".to_owned()).build();
+    assert_eq!(job.id, 3);
+    assert_eq!(job.command, "make");
+}
@@ -0,0 +1,103 @@
+// With `#[builder(env_prefix = "APP_")]` on the container, or `env = "..."` on
+// a field, the builder can be filled from text variables:
+//
+//     fn from_map(map: &HashMap<String, String>) -> Result<Self, EnvError>
+//     fn from_env() -> Result<Self, EnvError>
+//
+// Every field is read from the prefix followed by its name in upper case,
+// unless it names a variable of its own. Values are parsed with `FromStr` and
+// passed to the field's setter. An `each` field takes a comma-separated list,
+// with `key=value` items for a map. A variable that fails to parse is reported
+// together with every other one, and a missing variable leaves the field
+// unset for `build` to report. A field that cannot be parsed from text is left
+// out of `env_prefix` with `env = false`.
+
+use derive_builder::Builder;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone)]
+pub struct Tls {
+    pub cert: Vec<u8>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(env_prefix = "APP_")]
+pub struct Service {
+    host: String,
+    #[builder(env = "PORT")]
+    port: u16,
+    timeout: Option<u32>,
+    #[builder(default = "4")]
+    workers: usize,
+    #[builder(each = "peer")]
+    peers: Vec<String>,
+    #[builder(each = "limit")]
+    limits: BTreeMap<String, u32>,
+    #[builder(required)]
+    region: Option<String>,
+    #[builder(env = false)]
+    tls: Option<Tls>,
+    #[builder(env = false, default)]
+    labels: Vec<String>,
+}
+
+// Read from the real environment, under names that the process running the
+// test cannot have inherited.
+#[derive(Builder, Debug)]
+#[builder(env_prefix = "DERIVE_BUILDER_TEST_")]
+pub struct Probe {
+    host: String,
+    port: u16,
+}
+
+fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|&(var, value)| (var.to_owned(), value.to_owned()))
+        .collect()
+}
+
+fn main() {
+    let map = vars(&[
+        ("APP_HOST", "localhost"),
+        ("PORT", "8080"),
+        ("APP_TIMEOUT", "30"),
+        ("APP_PEERS", "a.example, b.example"),
+        ("APP_LIMITS", "cpu=2, memory=512"),
+        ("APP_REGION", "eu"),
+        ("APP_TLS", "ignored"),
+        ("APP_LABELS", "ignored"),
+    ]);
+    let service = ServiceBuilder::from_map(&map).unwrap().build().unwrap();
+    assert_eq!(service.host, "localhost");
+    assert_eq!(service.port, 8080);
+    assert_eq!(service.timeout, Some(30));
+    assert_eq!(service.workers, 4);
+    assert_eq!(service.peers, ["a.example", "b.example"]);
+    assert_eq!(service.limits["memory"], 512);
+    assert_eq!(service.region.as_deref(), Some("eu"));
+    assert!(service.tls.is_none());
+    assert!(service.labels.is_empty());
+
+    let builder = ServiceBuilder::from_map(&vars(&[("APP_HOST", "localhost")])).unwrap();
+    assert_eq!(builder.missing_fields(), ["port", "region"]);
+
+    let map = vars(&[
+        ("PORT", "http"),
+        ("APP_WORKERS", "-1"),
+        ("APP_LIMITS", "cpu"),
+    ]);
+    let err = ServiceBuilder::from_map(&map).err().unwrap();
+    let vars: Vec<_> = err.errors().iter().map(|error| error.var()).collect();
+    assert_eq!(vars, ["PORT", "APP_WORKERS", "APP_LIMITS"]);
+    assert_eq!(
+        err.errors()[0].to_string(),
+        "invalid `PORT` for field `port`: invalid digit found in string",
+    );
+
+    std::env::set_var("DERIVE_BUILDER_TEST_HOST", "example.com");
+    std::env::set_var("DERIVE_BUILDER_TEST_PORT", "443");
+    let probe = ProbeBuilder::from_env().unwrap().build().unwrap();
+    assert_eq!(probe.host, "example.com");
+    assert_eq!(probe.port, 443);
+}
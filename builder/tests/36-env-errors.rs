@@ -0,0 +1,17 @@
+// A field read from a variable is parsed as its own type, or as the `T` of an
+// `Option<T>`. A collection without `each` and an `optional` alias whose inner
+// type cannot be seen are reported at the field rather than at the derive.
+
+use derive_builder::Builder;
+
+type MaybePort = Option<u16>;
+
+#[derive(Builder)]
+#[builder(env_prefix = "APP_")]
+pub struct Service {
+    hosts: Vec<String>,
+    #[builder(optional)]
+    port: MaybePort,
+}
+
+fn main() {}
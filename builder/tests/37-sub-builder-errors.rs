@@ -0,0 +1,33 @@
+// The type of a `sub_builder` field has to derive Builder, a builder using
+// the owned pattern can only be nested in a builder using it too, and a
+// builder with `to_builder` needs `to_builder` on its sub-builders' types. All
+// are reported at the field's type.
+
+use derive_builder::Builder;
+
+pub struct Plain {
+    pub value: u32,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Upstream {
+    address: String,
+}
+
+#[derive(Builder)]
+pub struct Route {
+    #[builder(sub_builder)]
+    plain: Plain,
+    #[builder(sub_builder)]
+    upstream: Upstream,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned", to_builder)]
+pub struct Gateway {
+    #[builder(sub_builder)]
+    upstream: Upstream,
+}
+
+fn main() {}
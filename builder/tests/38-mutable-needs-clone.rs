@@ -0,0 +1,15 @@
+// The default mutable pattern builds from a reference to the builder, so
+// `build` clones every field out of it. A field type that is not `Clone`, like
+// a socket, cannot derive Builder with it, and is reported at the field. With
+// `pattern = "owned"`, `build` moves the fields out instead (see
+// 26-no-default.rs).
+
+use derive_builder::Builder;
+use std::net::TcpStream;
+
+#[derive(Builder)]
+pub struct Conn {
+    stream: Option<TcpStream>,
+}
+
+fn main() {}
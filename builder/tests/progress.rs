@@ -10,4 +10,34 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-missing-fields.rs");
+    t.pass("tests/11-generics.rs");
+    t.pass("tests/12-typestate.rs");
+    t.compile_fail("tests/13-typestate-missing-field.rs");
+    t.pass("tests/14-builder-pattern.rs");
+    t.pass("tests/15-default-value.rs");
+    t.pass("tests/16-setter-into.rs");
+    t.pass("tests/17-validate.rs");
+    t.pass("tests/18-enum.rs");
+    t.pass("tests/19-tuple-struct.rs");
+    t.compile_fail("tests/20-attribute-errors.rs");
+    t.pass("tests/21-each-collections.rs");
+    t.pass("tests/22-bulk-and-each-setters.rs");
+    t.pass("tests/23-sub-builder.rs");
+    t.pass("tests/24-custom-names.rs");
+    t.pass("tests/25-skip-and-private.rs");
+    t.pass("tests/26-no-default.rs");
+    #[cfg(feature = "serde")]
+    t.pass("tests/27-serde.rs");
+    t.pass("tests/28-try-setter.rs");
+    t.pass("tests/29-to-builder.rs");
+    t.pass("tests/30-no-std.rs");
+    t.pass("tests/31-qualified-paths.rs");
+    t.pass("tests/32-custom-error.rs");
+    t.pass("tests/33-introspection.rs");
+    t.pass("tests/34-constructor-arg.rs");
+    t.pass("tests/35-env.rs");
+    t.compile_fail("tests/36-env-errors.rs");
+    t.compile_fail("tests/37-sub-builder-errors.rs");
+    t.compile_fail("tests/38-mutable-needs-clone.rs");
 }